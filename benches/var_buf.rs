@@ -0,0 +1,138 @@
+//! Rough comparisons of `VarBufReader` against `std::io::BufReader`.
+//!
+//! Run with `cargo bench`. This doesn't use the (unstable) `test` harness,
+//! it just prints the best of a few runs.
+
+use std::hint::black_box;
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::time::Duration;
+use std::time::Instant;
+
+use iowrap::VarBufRead;
+use iowrap::VarBufReader;
+
+const RUNS: usize = 5;
+
+fn lines() -> Vec<u8> {
+    let mut data = Vec::new();
+    for i in 0..1_000_000u64 {
+        data.extend_from_slice(format!("{} some log line of a typical-ish length\n", i).as_bytes());
+    }
+    data
+}
+
+fn best_of(name: &str, bytes: usize, mut f: impl FnMut() -> usize) {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        black_box(f());
+        best = best.min(start.elapsed());
+    }
+    println!(
+        "{:40} {:>10.2?} {:>8.0} MiB/s",
+        name,
+        best,
+        bytes as f64 / best.as_secs_f64() / 1024. / 1024.
+    );
+}
+
+fn main() {
+    let data = lines();
+    let len = data.len();
+
+    best_of("std BufReader read_until", len, || {
+        let mut reader = io::BufReader::new(io::Cursor::new(&data));
+        let mut line = Vec::new();
+        let mut count = 0;
+        while reader.read_until(b'\n', &mut line).unwrap() != 0 {
+            count += line.len();
+            line.clear();
+        }
+        count
+    });
+
+    best_of("VarBufReader read_until", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut line = Vec::new();
+        let mut count = 0;
+        while reader.read_until(b'\n', &mut line).unwrap() != 0 {
+            count += line.len();
+            line.clear();
+        }
+        count
+    });
+
+    best_of("VarBufReader read_until_limit", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut count = 0;
+        while let Ok(line) = reader.read_until_limit(b'\n', 1024) {
+            count += line.len();
+        }
+        count
+    });
+
+    best_of("VarBufReader read_until_limit_borrowed", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut count = 0;
+        while let Ok(line) = reader.read_until_limit_borrowed(b'\n', 1024) {
+            count += line.len();
+        }
+        count
+    });
+
+    best_of("std BufReader 16-byte records", len, || {
+        let mut reader = io::BufReader::new(io::Cursor::new(&data));
+        let mut buf = [0u8; 16];
+        let mut count = 0;
+        while reader.read_exact(&mut buf).is_ok() {
+            count += usize::from(buf[0]);
+        }
+        count
+    });
+
+    best_of("VarBufReader 16-byte records", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut count = 0;
+        while let Ok(buf) = reader.fill_at_least(16) {
+            count += usize::from(buf[0]);
+            VarBufRead::consume(&mut reader, 16);
+        }
+        count
+    });
+
+    best_of("VarBufReader 1MiB fill, 16-byte consume", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut count = 0;
+        while let Ok(buf) = reader.fill_at_least(1024 * 1024) {
+            count += usize::from(buf[0]);
+            VarBufRead::consume(&mut reader, 16);
+        }
+        count
+    });
+
+    best_of("std BufReader 64KiB reads", len, || {
+        let mut reader = io::BufReader::new(io::Cursor::new(&data));
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut count = 0;
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break count,
+                read => count += read + usize::from(black_box(&buf)[0]),
+            }
+        }
+    });
+
+    best_of("VarBufReader 64KiB reads", len, || {
+        let mut reader = VarBufReader::new(io::Cursor::new(&data));
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut count = 0;
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break count,
+                read => count += read + usize::from(black_box(&buf)[0]),
+            }
+        }
+    });
+}
@@ -0,0 +1,228 @@
+use std::io;
+
+use crate::var_buf::VarBufRead;
+
+/// The most bytes a `u64` can take up as a LEB128 varint.
+const MAX_LEB128_LEN: usize = 10;
+
+macro_rules! decode_int {
+    ($($ty:ident $peek_le:ident $read_le:ident $peek_be:ident $read_be:ident;)*) => {
+        $(
+            #[doc = concat!("Decode a little-endian `", stringify!($ty), "`, without consuming it.")]
+            fn $peek_le(&mut self) -> io::Result<$ty> {
+                Ok($ty::from_le_bytes(self.peek_fixed()?))
+            }
+
+            #[doc = concat!("Decode and consume a little-endian `", stringify!($ty), "`.")]
+            fn $read_le(&mut self) -> io::Result<$ty> {
+                Ok($ty::from_le_bytes(self.read_fixed()?))
+            }
+
+            #[doc = concat!("Decode a big-endian `", stringify!($ty), "`, without consuming it.")]
+            fn $peek_be(&mut self) -> io::Result<$ty> {
+                Ok($ty::from_be_bytes(self.peek_fixed()?))
+            }
+
+            #[doc = concat!("Decode and consume a big-endian `", stringify!($ty), "`.")]
+            fn $read_be(&mut self) -> io::Result<$ty> {
+                Ok($ty::from_be_bytes(self.read_fixed()?))
+            }
+        )*
+    };
+}
+
+/// Decode binary values directly from a `VarBufRead`'s buffer.
+///
+/// Each method checks the length once, with `fill_at_least`, so will fail with
+/// `ErrorKind::UnexpectedEof` if the stream is too short, in which case nothing is consumed.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use iowrap::VarBufDecode;
+/// use iowrap::VarBufReader;
+///
+/// let mut reader = VarBufReader::new(io::Cursor::new(b"\x00\x01\xac\x02\x03"));
+/// assert_eq!(1, reader.read_u16_be().unwrap());
+/// assert_eq!(300, reader.read_varint_leb128().unwrap());
+/// assert_eq!(io::ErrorKind::UnexpectedEof, reader.read_u16_le().unwrap_err().kind());
+/// assert_eq!(-2, reader.read_zigzag().unwrap());
+/// ```
+pub trait VarBufDecode: VarBufRead {
+    /// Copy the next `N` bytes out, without consuming them.
+    fn peek_fixed<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let buf = self.fill_at_least(N)?;
+        let mut ret = [0u8; N];
+        ret.copy_from_slice(&buf[..N]);
+        Ok(ret)
+    }
+
+    /// Copy out and consume the next `N` bytes.
+    fn read_fixed<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let ret = self.peek_fixed()?;
+        self.consume(N);
+        Ok(ret)
+    }
+
+    /// The next byte, without consuming it.
+    fn peek_u8(&mut self) -> io::Result<u8> {
+        Ok(self.peek_fixed::<1>()?[0])
+    }
+
+    /// Consume the next byte.
+    fn read_u8(&mut self) -> io::Result<u8> {
+        Ok(self.read_fixed::<1>()?[0])
+    }
+
+    decode_int! {
+        u16 peek_u16_le read_u16_le peek_u16_be read_u16_be;
+        i16 peek_i16_le read_i16_le peek_i16_be read_i16_be;
+        u32 peek_u32_le read_u32_le peek_u32_be read_u32_be;
+        i32 peek_i32_le read_i32_le peek_i32_be read_i32_be;
+        u64 peek_u64_le read_u64_le peek_u64_be read_u64_be;
+        i64 peek_i64_le read_i64_le peek_i64_be read_i64_be;
+    }
+
+    /// Decode and consume an unsigned LEB128 varint, as used by e.g. protobuf.
+    ///
+    /// Values which don't fit in a `u64` are rejected with `ErrorKind::InvalidData`.
+    fn read_varint_leb128(&mut self) -> io::Result<u64> {
+        let (value, len) = leb128(self.fill_many(MAX_LEB128_LEN)?)?;
+        self.consume(len);
+        Ok(value)
+    }
+
+    /// Decode and consume a zigzag-encoded signed varint, as used by e.g. protobuf's `sint64`.
+    fn read_zigzag(&mut self) -> io::Result<i64> {
+        let value = self.read_varint_leb128()?;
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+}
+
+impl<T: VarBufRead + ?Sized> VarBufDecode for T {}
+
+/// Decode an unsigned LEB128 varint from the start of `buf`,
+/// returning the value and how many bytes it took up.
+pub(crate) fn leb128(buf: &[u8]) -> io::Result<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in buf.iter().take(MAX_LEB128_LEN).enumerate() {
+        if i == MAX_LEB128_LEN - 1 && byte > 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "varint is too large for a u64",
+            ));
+        }
+
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+
+    // the last possible byte either ends the varint, or is too large,
+    // so we only get here if we ran out of bytes
+    Err(io::ErrorKind::UnexpectedEof.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Cursor;
+
+    use crate::ShortRead;
+    use crate::VarBufReader;
+
+    use super::VarBufDecode;
+
+    #[test]
+    fn ints() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"\x01\x02\x03\x04\x05\x06\x07\x08\xff\xfe"),
+            vec![1, 0, 1, 2, 99].into_iter(),
+        ));
+        assert_eq!(0x0102, vb.peek_u16_be().unwrap());
+        assert_eq!(0x0201, vb.peek_u16_le().unwrap());
+        assert_eq!(0x04030201, vb.peek_u32_le().unwrap());
+        assert_eq!(0x0102030405060708, vb.read_u64_be().unwrap());
+        assert_eq!(-2, vb.peek_i16_be().unwrap());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            vb.read_u32_le().unwrap_err().kind()
+        );
+        assert_eq!(b"\xff\xfe", vb.buffer(), "nothing consumed");
+        assert_eq!(0xff, vb.read_u8().unwrap());
+        assert_eq!([0xfe], vb.read_fixed().unwrap());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            vb.peek_u8().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn fixed() {
+        let mut data: &[u8] = b"hello world";
+        assert_eq!(*b"hello", data.peek_fixed().unwrap());
+        assert_eq!(*b"hello", data.read_fixed().unwrap());
+        assert_eq!(*b" world", data.read_fixed().unwrap());
+        assert_eq!([0u8; 0], data.read_fixed().unwrap());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            data.read_fixed::<1>().unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn varint() {
+        let mut data: &[u8] = b"\x00\x7f\x80\x01\xac\x02\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01";
+        assert_eq!(0, data.read_varint_leb128().unwrap());
+        assert_eq!(127, data.read_varint_leb128().unwrap());
+        assert_eq!(128, data.read_varint_leb128().unwrap());
+        assert_eq!(300, data.read_varint_leb128().unwrap());
+        assert_eq!(u64::MAX, data.read_varint_leb128().unwrap());
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn varint_errors() {
+        let mut data: &[u8] = b"\xff\xff\xff\xff\xff\xff\xff\xff\xff\x02";
+        let e = data.read_varint_leb128().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        assert_eq!(
+            "varint is too large for a u64",
+            e.to_string(),
+            "bits past the 64th"
+        );
+
+        // an eleventh byte would be needed, but the continuation bit alone is too large
+        let mut data: &[u8] = b"\x80\x80\x80\x80\x80\x80\x80\x80\x80\x80\x00";
+        let e = data.read_varint_leb128().unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, e.kind());
+        assert_eq!(
+            "varint is too large for a u64",
+            e.to_string(),
+            "continued past the tenth byte"
+        );
+
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"\x80\x80"),
+            vec![1, 0, 1, 1].into_iter(),
+        ));
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            vb.read_varint_leb128().unwrap_err().kind()
+        );
+        assert_eq!(b"\x80\x80", vb.buffer(), "nothing consumed");
+    }
+
+    #[test]
+    fn zigzag() {
+        let mut data: &[u8] = b"\x00\x01\x02\x03\xfe\xff\xff\xff\xff\xff\xff\xff\xff\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01";
+        assert_eq!(0, data.read_zigzag().unwrap());
+        assert_eq!(-1, data.read_zigzag().unwrap());
+        assert_eq!(1, data.read_zigzag().unwrap());
+        assert_eq!(-2, data.read_zigzag().unwrap());
+        assert_eq!(i64::MAX, data.read_zigzag().unwrap());
+        assert_eq!(i64::MIN, data.read_zigzag().unwrap());
+    }
+}
@@ -1,5 +1,15 @@
 use std::io;
+use std::io::BufRead;
+use std::io::IoSliceMut;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+
+use crate::utf8::append_to_string;
+use crate::var_buf::CapacityExceeded;
+
+/// The default limit on how many bytes `peek` can hold.
+const DEFAULT_MAX_PEEK: usize = 1024;
 
 /// Track whether a stream has hit the end of file.
 ///
@@ -11,6 +21,11 @@ use std::io::Read;
 /// `Eof::eof()` and – for consistency – the `Read` implementation of `Eof`
 /// ignore `ErrorKind::Interrupted` errors and retry the operation.
 ///
+/// If the underlying reader is a `BufRead`, so is `Eof`, and `eof_buffered()` can
+/// check for the end by looking in its buffer, without holding a byte.
+///
+/// Seeking takes the held bytes into account, and discards them.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -39,39 +54,109 @@ use std::io::Read;
 /// ```
 pub struct Eof<R: Read> {
     inner: R,
-    next: Option<u8>,
+    held: Vec<u8>,
+    pos: usize,
+    max_peek: usize,
 }
 
 impl<R: Read> Eof<R> {
     pub fn new(inner: R) -> Self {
-        Eof { inner, next: None }
+        Eof {
+            inner,
+            held: Vec::new(),
+            pos: 0,
+            max_peek: DEFAULT_MAX_PEEK,
+        }
+    }
+
+    /// Allow `peek` to look up to `max_peek` bytes ahead; the default is 1KiB.
+    ///
+    /// `eof()` can always hold one byte, whatever the limit.
+    ///
+    /// # Panics
+    ///
+    /// If more than `max_peek` bytes are already held.
+    pub fn with_max_peek(mut self, max_peek: usize) -> Self {
+        assert!(
+            self.held().len() <= max_peek,
+            "{} bytes are already held",
+            self.held().len()
+        );
+        self.max_peek = max_peek;
+        self
+    }
+
+    /// The most bytes `peek` can look ahead.
+    pub fn max_peek(&self) -> usize {
+        self.max_peek
     }
 
     /// Test if we are at the end of the stream.
     /// If false, then a proceeding `read()` will always succeed.
     pub fn eof(&mut self) -> io::Result<bool> {
-        if self.next.is_some() {
-            return Ok(false);
+        Ok(self.fill_held(1)?.is_empty())
+    }
+
+    /// Look at the next `n` bytes, without consuming them.
+    ///
+    /// This reads from the underlying stream until it has `n` bytes, so will only
+    /// return fewer at the end of the stream. The bytes are held, and returned
+    /// by subsequent reads, before any more of the underlying stream.
+    ///
+    /// Asking for more than `max_peek()` bytes fails with a `CapacityExceeded`, inside
+    /// an `io::Error` of kind `ErrorKind::OutOfMemory`, without reading anything.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::io;
+    /// # use std::io::Read;
+    /// use iowrap::Eof;
+    ///
+    /// let mut file = Eof::new(io::Cursor::new(b"\x1f\x8b\x08\x00"));
+    /// if file.peek(2).unwrap() == b"\x1f\x8b" {
+    ///     // it's gzip
+    /// }
+    /// let mut buf = Vec::new();
+    /// file.read_to_end(&mut buf).unwrap();
+    /// assert_eq!(4, buf.len());
+    /// ```
+    pub fn peek(&mut self, n: usize) -> io::Result<&[u8]> {
+        if n > self.max_peek {
+            return Err(CapacityExceeded::new(n, self.max_peek).into());
         }
 
-        let mut buf = [0u8; 1];
-        Ok(loop {
-            break match self.inner.read(&mut buf) {
-                Ok(0) => true,
-                Ok(1) => {
-                    self.next = Some(buf[0]);
-                    false
+        self.fill_held(n)
+    }
+
+    /// Read until `n` bytes are held, or the stream ends, and return up to `n` of them.
+    fn fill_held(&mut self, n: usize) -> io::Result<&[u8]> {
+        if self.held().len() < n {
+            self.held.drain(..self.pos);
+            self.pos = 0;
+
+            while self.held.len() < n {
+                let filled = self.held.len();
+                self.held.resize(n, 0);
+                let ret = self.inner.read(&mut self.held[filled..]);
+                self.held
+                    .truncate(filled + ret.as_ref().map_or(0, |count| *count));
+                match ret {
+                    Ok(0) => break,
+                    Ok(_) => (),
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+                    Err(e) => return Err(e),
                 }
-                Ok(_) => unreachable!(),
-                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                Err(e) => return Err(e),
-            };
-        })
+            }
+        }
+
+        let held = self.held();
+        Ok(&held[..n.min(held.len())])
     }
 
-    /// The buffered value, which we read while checking for EOF.
+    /// The first of the held bytes, which we read while checking for EOF.
     pub fn held_state(&self) -> Option<u8> {
-        self.next
+        self.held().first().copied()
     }
 
     pub fn get_ref(&self) -> &R {
@@ -82,59 +167,206 @@ impl<R: Read> Eof<R> {
         &mut self.inner
     }
 
+    /// Any held bytes are lost.
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    fn held(&self) -> &[u8] {
+        &self.held[self.pos..]
+    }
+
+    fn discard_held(&mut self, amt: usize) {
+        self.pos += amt;
+        if self.pos == self.held.len() {
+            self.held.clear();
+            self.pos = 0;
+        }
+    }
+}
+
+impl<R: BufRead> Eof<R> {
+    /// Test if we are at the end of the stream, using the underlying reader's buffer,
+    /// instead of taking a byte out of it, as `eof()` does.
+    ///
+    /// Rust can't (yet) pick this implementation for `eof()` automatically when the
+    /// underlying reader is a `BufRead`, so it has a different name.
+    pub fn eof_buffered(&mut self) -> io::Result<bool> {
+        loop {
+            match self.fill_buf() {
+                Ok(buf) => return Ok(buf.is_empty()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 impl<R: Read> Read for Eof<R> {
     /// For consistency with `eof()`, this implementation retries the
     /// operation on `ErrorKind::Interrupted` errors.
+    ///
+    /// Any held bytes are returned first, then the rest of `buf` is filled by one
+    /// read from the underlying stream. If that read fails, the held bytes are still
+    /// returned; the error will, presumably, be returned by the next read.
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         if buf.is_empty() {
             return Ok(0);
         }
 
-        if let Some(val) = self.next {
-            buf[0] = val;
-            self.next = None;
-            return Ok(1);
+        let held = buf.len().min(self.held().len());
+        buf[..held].copy_from_slice(&self.held()[..held]);
+        self.discard_held(held);
+        if held == buf.len() {
+            return Ok(held);
         }
 
         loop {
-            match self.inner.read(buf) {
+            match self.inner.read(&mut buf[held..]) {
+                Ok(count) => return Ok(held + count),
                 Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
-                e => return e,
+                Err(_) if held > 0 => return Ok(held),
+                Err(e) => return Err(e),
             }
         }
     }
+
+    /// Like `read`, held bytes are returned first, then the rest of the buffers are
+    /// filled by one vectored read from the underlying stream.
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        if self.held().is_empty() {
+            loop {
+                match self.inner.read_vectored(bufs) {
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    e => return e,
+                }
+            }
+        }
+
+        let mut held = 0;
+        let mut rest = Vec::with_capacity(bufs.len());
+        for buf in bufs.iter_mut() {
+            let len = buf.len().min(self.held().len());
+            buf[..len].copy_from_slice(&self.held()[..len]);
+            self.discard_held(len);
+            held += len;
+            if len < buf.len() {
+                rest.push(IoSliceMut::new(&mut buf[len..]));
+            }
+        }
+        if rest.is_empty() {
+            return Ok(held);
+        }
+
+        loop {
+            match self.inner.read_vectored(&mut rest) {
+                Ok(count) => return Ok(held + count),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) if held > 0 => return Ok(held),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let held = self.held().len();
+        buf.extend_from_slice(self.held());
+        self.discard_held(held);
+        Ok(held + self.inner.read_to_end(buf)?)
+    }
+
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        append_to_string(buf, |bytes| self.read_to_end(bytes))
+    }
+}
+
+/// Held bytes, from `eof()` or `peek()`, are returned first, on their own.
+///
+/// Unlike `read`, `fill_buf` doesn't retry on `ErrorKind::Interrupted` errors, as the
+/// borrow checker won't allow it without risking a second read at the end of the stream.
+/// `read_line` and friends retry for themselves.
+impl<R: BufRead> BufRead for Eof<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if !self.held().is_empty() {
+            return Ok(self.held());
+        }
+
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let held = amt.min(self.held().len());
+        self.discard_held(held);
+        self.inner.consume(amt - held);
+    }
+}
+
+impl<R: Read + Seek> Seek for Eof<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            // the underlying stream is ahead of us by the held bytes
+            SeekFrom::Current(offset) => SeekFrom::Current(
+                i64::try_from(self.held().len())
+                    .ok()
+                    .and_then(|held| offset.checked_sub(held))
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "seek offset overflows")
+                    })?,
+            ),
+            pos => pos,
+        };
+
+        let position = self.inner.seek(pos)?;
+        self.held.clear();
+        self.pos = 0;
+        Ok(position)
+    }
+
+    /// Doesn't discard the held bytes.
+    ///
+    /// If the underlying reader has been moved back, through `get_mut`, to before
+    /// the held bytes, there is no position, and this errors.
+    fn stream_position(&mut self) -> io::Result<u64> {
+        self.inner
+            .stream_position()?
+            .checked_sub(self.held().len() as u64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the underlying reader is behind the held bytes",
+                )
+            })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Eof;
+    use super::DEFAULT_MAX_PEEK;
     use crate::short::ShortRead;
+    use crate::CapacityExceeded;
     use std::io;
+    use std::io::BufRead;
+    use std::io::IoSliceMut;
     use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
 
     #[test]
     fn smoke_cursor() {
         let mut eof = Eof::new(io::Cursor::new(vec![7, 8, 9, 10, 11, 12]));
         assert_eq!(None, eof.held_state(), "nothing held initially");
-        assert_eq!(
-            false,
-            eof.eof().unwrap(),
+        assert!(
+            !eof.eof().unwrap(),
             "there's bytes to read, we're not at the end"
         );
-        assert_eq!(
-            false,
-            eof.eof().unwrap(),
+        assert!(
+            !eof.eof().unwrap(),
             "we weren't at the end before, so we're not now"
         );
         assert_eq!(Some(7), eof.held_state(), "the state is the first byte");
-        assert_eq!(
-            false,
-            eof.eof().unwrap(),
+        assert!(
+            !eof.eof().unwrap(),
             "viewing the state doesn't move us to the end"
         );
 
@@ -142,9 +374,8 @@ mod tests {
         eof.read_exact(&mut buf).unwrap();
 
         assert_eq!(None, eof.held_state(), "reading consumed the state");
-        assert_eq!(
-            false,
-            eof.eof().unwrap(),
+        assert!(
+            !eof.eof().unwrap(),
             "reading two bytes didn't push us past the end"
         );
         assert_eq!(
@@ -155,24 +386,23 @@ mod tests {
 
         let mut buf = [0u8; 20];
         assert_eq!(
-            1,
+            4,
             eof.read(&mut buf).unwrap(),
-            "[implementation detail] read will only return one byte"
+            "read returns the held byte, then carries on"
         );
-        assert_eq!(9, buf[0], "it was the right byte");
-        assert_eq!(3, eof.read(&mut buf).unwrap(), "there's three more bytes");
+        assert_eq!(&[9, 10, 11, 12], &buf[..4], "they were the right bytes");
+        assert_eq!(0, eof.read(&mut buf).unwrap(), "there's nothing more");
         assert_eq!(
             None,
             eof.held_state(),
             "there's no state after some reading"
         );
-        assert_eq!(true, eof.eof().unwrap(), "we're at the end");
+        assert!(eof.eof().unwrap(), "we're at the end");
         assert_eq!(None, eof.held_state(), "there's still no state");
 
         eof.get_mut().get_mut().push(100);
-        assert_eq!(
-            false,
-            eof.eof().unwrap(),
+        assert!(
+            !eof.eof().unwrap(),
             "if the underlying reader starts returning data again, so do we"
         );
     }
@@ -187,27 +417,321 @@ mod tests {
 
         let mut buf = [0u8; 2];
 
+        assert!(!eof.eof().unwrap(), "skip interruption at the beginning");
         assert_eq!(
-            false,
-            eof.eof().unwrap(),
-            "skip interruption at the beginning"
+            2,
+            eof.read(&mut buf).unwrap(),
+            "skip interruption while reading after the held byte"
         );
-        assert_eq!(1, eof.read(&mut buf).unwrap());
-        assert_eq!(b'1', buf[0]);
+        assert_eq!(b"12", &buf);
 
+        assert!(!eof.eof().unwrap(), "skip multiple interruptions");
+        assert_eq!(2, eof.read(&mut buf).unwrap());
+        assert_eq!(b"34", &buf);
         assert_eq!(
             1,
             eof.read(&mut buf).unwrap(),
             "skip interruption while reading"
         );
-        assert_eq!(b'2', buf[0]);
+        assert_eq!(b'5', buf[0]);
+
+        assert!(eof.eof().unwrap(), "skip interruption before eof");
+    }
+
+    #[test]
+    fn read_after_held() {
+        let mut eof = Eof::new(ShortRead::new(
+            io::Cursor::new(b"hello world"),
+            vec![1, 3, 99, 99].into_iter(),
+        ));
+        assert!(!eof.eof().unwrap());
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            4,
+            eof.read(&mut buf).unwrap(),
+            "the held byte, and a short read"
+        );
+        assert_eq!(b"hell", &buf[..4]);
+
+        assert_eq!(b"o ", eof.peek(2).unwrap());
+        let mut buf = [0u8; 2];
+        assert_eq!(2, eof.read(&mut buf).unwrap(), "no read needed");
+        assert_eq!(b"o ", &buf);
+        assert_eq!(b"world", eof.peek(5).unwrap());
+    }
 
-        assert_eq!(false, eof.eof().unwrap(), "skip multiple interruptions");
+    /// Return one byte, then fail.
+    struct OneThenFail(Option<u8>);
+
+    impl Read for OneThenFail {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.take() {
+                Some(b) => {
+                    buf[0] = b;
+                    Ok(1)
+                }
+                None => Err(io::ErrorKind::ConnectionReset.into()),
+            }
+        }
+    }
+
+    #[test]
+    fn error_after_held() {
+        let mut eof = Eof::new(OneThenFail(Some(7)));
+        assert!(!eof.eof().unwrap());
+        let mut buf = [0u8; 4];
+        assert_eq!(1, eof.read(&mut buf).unwrap(), "the held byte isn't lost");
+        assert_eq!(7, buf[0]);
+        assert_eq!(
+            io::ErrorKind::ConnectionReset,
+            eof.read(&mut buf).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut eof = Eof::new(io::BufReader::with_capacity(
+            4,
+            ShortRead::new(
+                io::Cursor::new(b"one\ntwo\nthree"),
+                vec![1, 0, 9, 9, 9, 9, 9].into_iter(),
+            ),
+        ));
+        assert!(!eof.eof_buffered().unwrap(), "retries the interruption");
+        assert_eq!(None, eof.held_state(), "nothing was taken");
+        assert_eq!(b"o", eof.fill_buf().unwrap());
+
+        let mut line = String::new();
+        eof.read_line(&mut line).unwrap();
+        assert_eq!("one\n", line);
+
+        assert!(!eof.eof().unwrap());
+        assert_eq!(Some(b't'), eof.held_state());
+        assert_eq!(b"t", eof.fill_buf().unwrap(), "held bytes first");
+        assert_eq!(b"tw", eof.peek(2).unwrap());
+        eof.consume(1);
+        assert_eq!(b"w", eof.fill_buf().unwrap());
+
+        let lines: Vec<String> = (&mut eof).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(vec!["wo", "three"], lines);
+        assert!(eof.eof_buffered().unwrap());
+        assert!(eof.eof().unwrap());
+    }
+
+    #[test]
+    fn seek() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello world"));
+        assert!(!eof.eof().unwrap());
+        assert_eq!(
+            0,
+            eof.stream_position().unwrap(),
+            "the held byte wasn't read"
+        );
+        assert_eq!(Some(b'h'), eof.held_state(), "and is still held");
+
+        assert_eq!(2, eof.seek(SeekFrom::Current(2)).unwrap());
+        assert_eq!(None, eof.held_state(), "seeking discards");
+        assert!(!eof.eof().unwrap());
+        assert_eq!(2, eof.stream_position().unwrap());
+
+        assert_eq!(b"llo w", eof.peek(5).unwrap());
+        assert_eq!(1, eof.seek(SeekFrom::Current(-1)).unwrap());
+        let mut buf = [0u8; 4];
+        eof.read_exact(&mut buf).unwrap();
+        assert_eq!(b"ello", &buf);
+        assert_eq!(5, eof.stream_position().unwrap());
+
+        assert_eq!(b" ", eof.peek(1).unwrap());
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            eof.seek(SeekFrom::Current(-6)).unwrap_err().kind()
+        );
+        assert_eq!(Some(b' '), eof.held_state(), "failed seeks don't discard");
+        assert_eq!(5, eof.stream_position().unwrap());
+
+        assert_eq!(9, eof.seek(SeekFrom::End(-2)).unwrap());
+        assert_eq!(b"ld", eof.peek(5).unwrap());
+        assert_eq!(0, eof.seek(SeekFrom::Start(0)).unwrap());
+        assert_eq!(b"he", eof.peek(2).unwrap());
+
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            eof.seek(SeekFrom::Current(i64::MIN)).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn stream_position_behind_held() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello world"));
+        assert_eq!(b"hello", eof.peek(5).unwrap());
+        eof.get_mut().set_position(0);
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            eof.stream_position().unwrap_err().kind()
+        );
+        assert_eq!(b"hello", eof.peek(5).unwrap(), "nothing was discarded");
+
+        eof.get_mut().set_position(7);
+        assert_eq!(2, eof.stream_position().unwrap());
+    }
+
+    #[test]
+    fn max_peek() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello")).with_max_peek(4);
+        assert_eq!(4, eof.max_peek());
+        assert_eq!(b"hell", eof.peek(4).unwrap());
+
+        let e = eof.peek(5).unwrap_err();
+        assert_eq!(io::ErrorKind::OutOfMemory, e.kind());
+        let cause = e
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CapacityExceeded>())
+            .expect("typed error");
+        assert_eq!((5, 4), (cause.requested(), cause.limit()));
+        assert_eq!(
+            io::ErrorKind::OutOfMemory,
+            eof.peek(usize::MAX).unwrap_err().kind()
+        );
+        assert_eq!(b"hell", eof.peek(4).unwrap(), "nothing changed");
+
+        let mut eof = Eof::new(io::Cursor::new(b"tiny"));
+        assert_eq!(DEFAULT_MAX_PEEK, eof.max_peek());
+        assert_eq!(b"tiny", eof.peek(DEFAULT_MAX_PEEK).unwrap());
+
+        let mut eof = Eof::new(io::Cursor::new(b"a")).with_max_peek(0);
+        assert!(!eof.eof().unwrap(), "eof() can always hold a byte");
+        assert_eq!(io::ErrorKind::OutOfMemory, eof.peek(1).unwrap_err().kind());
+        let mut buf = [0u8; 2];
         assert_eq!(1, eof.read(&mut buf).unwrap());
-        assert_eq!(b'3', buf[0]);
-        assert_eq!(2, eof.read(&mut buf).unwrap());
-        assert_eq!(b"45", &buf);
+        assert!(eof.eof().unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn max_peek_below_held() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello"));
+        eof.peek(5).unwrap();
+        let _ = eof.with_max_peek(4);
+    }
+
+    #[test]
+    fn read_vectored_after_held() {
+        let mut eof = Eof::new(ShortRead::new(
+            io::Cursor::new(b"hello world"),
+            vec![1, 0, 3, 99].into_iter(),
+        ));
+        assert!(!eof.eof().unwrap());
+        let (mut a, mut b) = ([0u8; 2], [0u8; 8]);
+        assert_eq!(
+            4,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "the held byte, then a short read, after an interruption"
+        );
+        assert_eq!(b"he", &a);
+        assert_eq!(b"ll", &b[..2]);
+
+        let mut eof = Eof::new(OneThenFail(Some(7)));
+        assert!(!eof.eof().unwrap());
+        let (mut a, mut b) = ([0u8; 1], [0u8; 4]);
+        assert_eq!(
+            1,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "the held byte isn't lost"
+        );
+        assert_eq!(7, a[0]);
+        assert_eq!(
+            io::ErrorKind::ConnectionReset,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut b)])
+                .unwrap_err()
+                .kind()
+        );
+    }
+
+    #[test]
+    fn peek() {
+        let mut eof = Eof::new(ShortRead::new(
+            io::Cursor::new(b"PK\x03\x04rest"),
+            vec![1, 0, 2, 9].into_iter(),
+        ));
+        assert_eq!(b"PK\x03", eof.peek(3).unwrap(), "retried until long enough");
+        assert_eq!(Some(b'P'), eof.held_state());
+        assert_eq!(b"PK", eof.peek(2).unwrap(), "shorter peeks need no reading");
+
+        let mut buf = [0u8; 2];
+        eof.read_exact(&mut buf).unwrap();
+        assert_eq!(b"PK", &buf);
+        assert_eq!(Some(b'\x03'), eof.held_state());
+
+        assert_eq!(b"\x03\x04rest", eof.peek(10).unwrap(), "short at the end");
+        assert!(!eof.eof().unwrap());
+
+        let mut buf = Vec::new();
+        assert_eq!(6, eof.read_to_end(&mut buf).unwrap());
+        assert_eq!(b"\x03\x04rest", buf.as_slice());
+        assert!(eof.peek(4).unwrap().is_empty());
+        assert!(eof.eof().unwrap());
+        assert_eq!(None, eof.held_state());
+    }
+
+    #[test]
+    fn peek_then_read() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello world"));
+        assert_eq!(b"hello", eof.peek(5).unwrap());
+
+        let mut buf = [0u8; 3];
+        assert_eq!(3, eof.read(&mut buf).unwrap());
+        assert_eq!(b"hel", &buf);
+        assert_eq!(
+            b"lo w",
+            eof.peek(4).unwrap(),
+            "topped up, keeping what's held"
+        );
+
+        let (mut a, mut b) = ([0u8; 3], [0u8; 3]);
+        assert_eq!(
+            6,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "the held bytes, then the rest"
+        );
+        assert_eq!((b"lo ", b"wor"), (&a, &b));
+        assert_eq!(b"ld", eof.peek(99).unwrap());
+    }
+
+    #[test]
+    fn whole_reads() {
+        let mut eof = Eof::new(io::Cursor::new(b"hello world"));
+        assert!(!eof.eof().unwrap());
+        let (mut a, mut b) = ([0u8; 0], [0u8; 3]);
+        assert_eq!(
+            3,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "the held byte goes in the first non-empty buffer, then carries on"
+        );
+        assert_eq!(b"hel", &b);
+
+        let (mut a, mut b) = ([0u8; 2], [0u8; 2]);
+        assert_eq!(
+            4,
+            eof.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap()
+        );
+        assert_eq!((b"lo", b" w"), (&a, &b));
+
+        assert!(!eof.eof().unwrap());
+        let mut buf = Vec::new();
+        assert_eq!(4, eof.read_to_end(&mut buf).unwrap());
+        assert_eq!(b"orld", buf.as_slice());
+        assert!(eof.eof().unwrap());
 
-        assert_eq!(true, eof.eof().unwrap(), "skip interruption before eof");
+        let mut eof = Eof::new(io::Cursor::new("£1"));
+        assert!(!eof.eof().unwrap());
+        assert_eq!(Some(0xc2), eof.held_state(), "half a character");
+        let mut s = String::new();
+        assert_eq!(3, eof.read_to_string(&mut s).unwrap());
+        assert_eq!("£1", s);
     }
 }
@@ -0,0 +1,342 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::decode::leb128;
+use crate::var_buf::Record;
+use crate::var_buf::VarBufRead;
+
+/// How the length of each frame is written, before the frame itself.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Prefix {
+    U8,
+    U16Le,
+    U16Be,
+    U32Le,
+    U32Be,
+    U64Le,
+    U64Be,
+    /// An unsigned LEB128 varint, as used by e.g. protobuf's delimited messages.
+    Leb128,
+}
+
+impl Prefix {
+    /// The most bytes this prefix can take up.
+    fn max_len(self) -> usize {
+        match self {
+            Prefix::U8 => 1,
+            Prefix::U16Le | Prefix::U16Be => 2,
+            Prefix::U32Le | Prefix::U32Be => 4,
+            Prefix::U64Le | Prefix::U64Be => 8,
+            Prefix::Leb128 => 10,
+        }
+    }
+
+    /// Decode the length from the start of `buf`, returning it,
+    /// and how many bytes the prefix took up.
+    fn decode(self, buf: &[u8]) -> io::Result<(u64, usize)> {
+        if let Prefix::Leb128 = self {
+            return leb128(buf).map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => FrameError::TruncatedPrefix.into(),
+                _ => e,
+            });
+        }
+
+        let len = self.max_len();
+        if buf.len() < len {
+            return Err(FrameError::TruncatedPrefix.into());
+        }
+
+        // zero-extend to a u64 in whichever byte order
+        let mut bytes = [0u8; 8];
+        let value = match self {
+            Prefix::U16Be | Prefix::U32Be | Prefix::U64Be => {
+                bytes[8 - len..].copy_from_slice(&buf[..len]);
+                u64::from_be_bytes(bytes)
+            }
+            _ => {
+                bytes[..len].copy_from_slice(&buf[..len]);
+                u64::from_le_bytes(bytes)
+            }
+        };
+        Ok((value, len))
+    }
+}
+
+/// The error payload when a frame can't be read.
+///
+/// This is returned inside an `io::Error`; the truncation errors are of kind
+/// `ErrorKind::UnexpectedEof`, and `TooLong` is `ErrorKind::InvalidData`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameError {
+    /// The stream ended part-way through a length prefix.
+    TruncatedPrefix,
+    /// The stream ended part-way through a frame of length `len`.
+    Truncated { len: u64, available: usize },
+    /// The length prefix was larger than the maximum allowed, or than could ever be buffered.
+    TooLong { len: u64, max: usize },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::TruncatedPrefix => write!(f, "stream ended inside a frame's length"),
+            FrameError::Truncated { len, available } => write!(
+                f,
+                "stream ended inside a frame: wanted {} bytes, but only {} remain",
+                len, available
+            ),
+            FrameError::TooLong { len, max } => write!(
+                f,
+                "frame of {} bytes is longer than the maximum of {} bytes",
+                len, max
+            ),
+        }
+    }
+}
+
+impl error::Error for FrameError {}
+
+impl From<FrameError> for io::Error {
+    fn from(e: FrameError) -> io::Error {
+        let kind = match e {
+            FrameError::TruncatedPrefix | FrameError::Truncated { .. } => {
+                io::ErrorKind::UnexpectedEof
+            }
+            FrameError::TooLong { .. } => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// Split a stream into length-prefixed frames.
+///
+/// A stream which ends cleanly, between frames, gives `Ok(None)` (or ends the iterator).
+/// A stream which ends part-way through a frame is a `FrameError`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use iowrap::Frames;
+/// use iowrap::Prefix;
+/// use iowrap::VarBufReader;
+///
+/// let reader = VarBufReader::new(io::Cursor::new(b"\x00\x02hi\x00\x05there"));
+/// let mut frames = Frames::new(reader, Prefix::U16Be, 1024);
+/// assert_eq!(b"hi", &*frames.next_frame().unwrap().unwrap());
+/// assert_eq!(Some(b"there".to_vec()), frames.next_frame_vec().unwrap());
+/// assert!(frames.next_frame().unwrap().is_none());
+/// ```
+pub struct Frames<B> {
+    inner: B,
+    prefix: Prefix,
+    max_len: usize,
+    done: bool,
+}
+
+impl<B: VarBufRead> Frames<B> {
+    /// Frames longer than `max_len` bytes (not including the prefix) are rejected
+    /// before any attempt is made to buffer them.
+    pub fn new(inner: B, prefix: Prefix, max_len: usize) -> Self {
+        Frames {
+            inner,
+            prefix,
+            max_len,
+            done: false,
+        }
+    }
+
+    /// Borrow the next frame from the reader's buffer, without the length prefix.
+    ///
+    /// The frame, and its prefix, are consumed when the `Record` is dropped.
+    /// If there's an error, nothing is consumed.
+    pub fn next_frame(&mut self) -> io::Result<Option<Record<'_, B>>> {
+        let buf = self.inner.fill_many(self.prefix.max_len())?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+
+        let (len, prefix_len) = self.prefix.decode(buf)?;
+        let total = match usize::try_from(len) {
+            Ok(frame_len) if frame_len <= self.max_len => prefix_len.checked_add(frame_len),
+            _ => None,
+        };
+        let total = total.ok_or(FrameError::TooLong {
+            len,
+            max: self.max_len,
+        })?;
+
+        let buf = self.inner.fill_many(total)?;
+        if buf.len() < total {
+            return Err(FrameError::Truncated {
+                len,
+                available: buf.len() - prefix_len,
+            }
+            .into());
+        }
+
+        Ok(Some(Record::new(&mut self.inner, prefix_len..total, total)))
+    }
+
+    /// Copy out and consume the next frame, without the length prefix.
+    pub fn next_frame_vec(&mut self) -> io::Result<Option<Vec<u8>>> {
+        Ok(self.next_frame()?.map(|frame| frame.to_vec()))
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+/// Iterate over the frames. After the end of the stream, or an error, this will only
+/// return `None`.
+impl<B: VarBufRead> Iterator for Frames<B> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<io::Result<Vec<u8>>> {
+        if self.done {
+            return None;
+        }
+
+        let ret = self.next_frame_vec().transpose();
+        if !matches!(ret, Some(Ok(_))) {
+            self.done = true;
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Cursor;
+
+    use crate::ShortRead;
+    use crate::VarBufReader;
+
+    use super::FrameError;
+    use super::Frames;
+    use super::Prefix;
+
+    fn frame_error(e: io::Error) -> (io::ErrorKind, FrameError) {
+        let kind = e.kind();
+        let inner = *e
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<FrameError>())
+            .expect("typed error");
+        (kind, inner)
+    }
+
+    #[test]
+    fn prefixes() {
+        for (prefix, data) in [
+            (Prefix::U8, &b"\x02hi\x00"[..]),
+            (Prefix::U16Le, b"\x02\x00hi\x00\x00"),
+            (Prefix::U16Be, b"\x00\x02hi\x00\x00"),
+            (Prefix::U32Le, b"\x02\x00\x00\x00hi\x00\x00\x00\x00"),
+            (Prefix::U32Be, b"\x00\x00\x00\x02hi\x00\x00\x00\x00"),
+            (
+                Prefix::U64Le,
+                b"\x02\x00\x00\x00\x00\x00\x00\x00hi\x00\x00\x00\x00\x00\x00\x00\x00",
+            ),
+            (
+                Prefix::U64Be,
+                b"\x00\x00\x00\x00\x00\x00\x00\x02hi\x00\x00\x00\x00\x00\x00\x00\x00",
+            ),
+            (Prefix::Leb128, b"\x02hi\x00"),
+        ] {
+            let frames: Vec<Vec<u8>> = Frames::new(data, prefix, 10)
+                .collect::<io::Result<_>>()
+                .unwrap();
+            assert_eq!(vec![b"hi".to_vec(), Vec::new()], frames, "{:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn short_reads() {
+        let reader = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"\x05hello\xac\x02"),
+            vec![1, 0, 2, 1, 1, 99].into_iter(),
+        ));
+        let mut frames = Frames::new(reader, Prefix::Leb128, 1000);
+        assert_eq!(b"hello", &*frames.next_frame().unwrap().unwrap());
+
+        let (kind, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(io::ErrorKind::UnexpectedEof, kind);
+        assert_eq!(
+            FrameError::Truncated {
+                len: 300,
+                available: 0
+            },
+            e
+        );
+        assert_eq!(b"\xac\x02", frames.get_ref().buffer(), "nothing consumed");
+    }
+
+    #[test]
+    fn truncated() {
+        let mut frames = Frames::new(&b"\x00\x05hel"[..], Prefix::U16Be, 10);
+        let (kind, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(io::ErrorKind::UnexpectedEof, kind);
+        assert_eq!(
+            FrameError::Truncated {
+                len: 5,
+                available: 3
+            },
+            e
+        );
+
+        let mut frames = Frames::new(&b"\x00"[..], Prefix::U16Be, 10);
+        let (kind, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(io::ErrorKind::UnexpectedEof, kind);
+        assert_eq!(FrameError::TruncatedPrefix, e);
+
+        let mut frames = Frames::new(&b"\x80"[..], Prefix::Leb128, 10);
+        let (_, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(FrameError::TruncatedPrefix, e);
+    }
+
+    #[test]
+    fn too_long() {
+        let mut frames = Frames::new(&b"\x00\x0bhello world"[..], Prefix::U16Be, 10);
+        let (kind, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(io::ErrorKind::InvalidData, kind);
+        assert_eq!(FrameError::TooLong { len: 11, max: 10 }, e);
+
+        // with no real maximum, the prefix and the frame still can't fit together
+        let mut frames = Frames::new(
+            &b"\xff\xff\xff\xff\xff\xff\xff\xffabc"[..],
+            Prefix::U64Le,
+            usize::MAX,
+        );
+        let (kind, e) = frame_error(frames.next_frame().unwrap_err());
+        assert_eq!(io::ErrorKind::InvalidData, kind);
+        assert_eq!(
+            FrameError::TooLong {
+                len: u64::MAX,
+                max: usize::MAX
+            },
+            e
+        );
+    }
+
+    #[test]
+    fn iterator_stops_after_error() {
+        let mut frames = Frames::new(&b"\x02hi\x05hel"[..], Prefix::U8, 10);
+        assert_eq!(b"hi", frames.next().unwrap().unwrap().as_slice());
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            frames.next().unwrap().unwrap_err().kind()
+        );
+        assert!(frames.next().is_none());
+    }
+}
@@ -1,17 +1,30 @@
 //! Some utility methods for wrapping `std::io::Read` and `std::io::Write`.
 
+mod decode;
 mod eof;
+mod frame;
 mod ignore;
+mod line_pos;
 mod many;
 mod pos;
+mod progress;
 mod short;
-#[cfg(test)]
+mod stats;
+mod unread;
+mod utf8;
 mod var_buf;
+mod var_buf_std;
 
+pub use crate::decode::VarBufDecode;
 pub use crate::eof::Eof;
+pub use crate::frame::{FrameError, Frames, Prefix};
 pub use crate::ignore::Ignore;
+pub use crate::line_pos::{LineEnding, LinePos};
 pub use crate::many::ReadMany;
-pub use crate::pos::Pos;
+pub use crate::pos::{Mark, Pos, PosError, PosHandle};
+pub use crate::progress::{Progress, Reporter};
 pub use crate::short::ShortRead;
-#[cfg(test)]
-pub use crate::var_buf::{VarBufRead, VarBufReader};
+pub use crate::stats::{CallStats, Stats};
+pub use crate::unread::Unread;
+pub use crate::var_buf::{CapacityExceeded, Delimiter, Record, Until, VarBufRead, VarBufReader};
+pub use crate::var_buf_std::VarBufAdaptor;
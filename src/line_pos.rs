@@ -0,0 +1,294 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+
+/// Which bytes `LinePos` considers to end a line.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Only `\n` ends a line. A `\r` before it is counted as part of the line.
+    Lf,
+    /// `\n`, `\r\n`, and a lone `\r`, each end one line.
+    Any,
+}
+
+/// Track the line and column reached in a text stream, for error messages.
+///
+/// Lines and columns are numbered from one, as people expect. The column is that
+/// of the next byte to be read, so, after a failed parse, it points at the problem.
+/// Columns are available both in bytes, and in UTF-8 characters (invalid UTF-8 is
+/// counted as best we can).
+///
+/// Like `Pos`, for `BufRead`, bytes are counted when they are `consume`d.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// # use std::io::BufRead;
+/// # use std::io::Read;
+/// use iowrap::LinePos;
+///
+/// let mut pos = LinePos::new(io::Cursor::new("key = value\nkéy = 🦀 = oops"));
+/// let mut line = String::new();
+/// pos.read_line(&mut line).unwrap();
+/// pos.read_exact(&mut [0u8; "kéy = 🦀 ".len()]).unwrap();
+/// assert_eq!((2, 13), (pos.line(), pos.column()));
+/// assert_eq!(9, pos.char_column());
+/// ```
+pub struct LinePos<R> {
+    inner: R,
+    counter: Counter,
+}
+
+/// Kept apart from `inner`, so we can count bytes we've borrowed from it.
+struct Counter {
+    line_ending: LineEnding,
+    position: u64,
+    line: u64,
+    line_start: u64,
+    chars: u64,
+    after_cr: bool,
+}
+
+impl<R> LinePos<R> {
+    pub fn new(inner: R) -> Self {
+        LinePos {
+            inner,
+            counter: Counter {
+                line_ending: LineEnding::Lf,
+                position: 0,
+                line: 1,
+                line_start: 0,
+                chars: 0,
+                after_cr: false,
+            },
+        }
+    }
+
+    /// Choose which bytes end a line; the default is `LineEnding::Lf`.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.counter.line_ending = line_ending;
+        self
+    }
+
+    /// The line the next byte is on, starting from one.
+    pub fn line(&self) -> u64 {
+        self.counter.line
+    }
+
+    /// The column of the next byte, in bytes, starting from one.
+    pub fn column(&self) -> u64 {
+        self.counter.position - self.counter.line_start + 1
+    }
+
+    /// The column of the next byte, in UTF-8 characters, starting from one.
+    pub fn char_column(&self) -> u64 {
+        self.counter.chars + 1
+    }
+
+    /// The byte offset of the start of the current line.
+    pub fn line_start(&self) -> u64 {
+        self.counter.line_start
+    }
+
+    /// The number of bytes successfully read from the stream.
+    pub fn position(&self) -> u64 {
+        self.counter.position
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading directly from the underlying reader won't be counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl Counter {
+    fn observe(&mut self, buf: &[u8]) {
+        for &b in buf {
+            self.position += 1;
+            match b {
+                b'\n' if self.after_cr => self.line_start = self.position,
+                b'\n' => self.new_line(),
+                b'\r' if LineEnding::Any == self.line_ending => {
+                    self.new_line();
+                    self.after_cr = true;
+                    continue;
+                }
+                // not a utf-8 continuation byte
+                _ if b & 0xc0 != 0x80 => self.chars += 1,
+                _ => (),
+            }
+            self.after_cr = false;
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.position;
+        self.chars = 0;
+    }
+}
+
+impl<R: Read> Read for LinePos<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.inner.read(buf)?;
+        self.counter.observe(&buf[..count]);
+        Ok(count)
+    }
+}
+
+impl<R: BufRead> BufRead for LinePos<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    /// The bytes must already be buffered, i.e. `fill_buf` must have just returned at
+    /// least `amt` bytes, as `BufRead` requires.
+    ///
+    /// # Panics
+    ///
+    /// If the buffer was empty, and filling it fails.
+    fn consume(&mut self, amt: usize) {
+        if 0 == amt {
+            // e.g. `read_line` at end-of-file; there may be nothing to look at
+            self.inner.consume(0);
+            return;
+        }
+
+        // the underlying buffer is non-empty, so this won't need to do any IO
+        let buf = self
+            .inner
+            .fill_buf()
+            .expect("LinePos::consume called without a filled buffer");
+        self.counter.observe(&buf[..amt.min(buf.len())]);
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::BufRead;
+    use std::io::Read;
+
+    use crate::ShortRead;
+    use crate::Stats;
+
+    use super::LineEnding;
+    use super::LinePos;
+
+    fn read_all<R: Read>(pos: &mut LinePos<R>) {
+        let mut buf = Vec::new();
+        pos.read_to_end(&mut buf).unwrap();
+    }
+
+    #[test]
+    fn lines() {
+        let mut pos = LinePos::new(ShortRead::new(
+            io::Cursor::new(b"ab\ncd\r\nef\rgh"),
+            vec![1, 2, 0, 1, 1, 1, 1].into_iter(),
+        ));
+        assert_eq!((1, 1, 0), (pos.line(), pos.column(), pos.line_start()));
+        let mut buf = [0u8; 3];
+        assert_eq!(1, pos.read(&mut buf).unwrap());
+        assert_eq!((1, 2), (pos.line(), pos.column()));
+        assert_eq!(2, pos.read(&mut buf).unwrap());
+        assert_eq!((2, 1, 3), (pos.line(), pos.column(), pos.line_start()));
+        pos.read(&mut buf).unwrap_err();
+        assert_eq!(1, pos.read(&mut buf).unwrap());
+        assert_eq!(1, pos.read(&mut buf).unwrap());
+        assert_eq!(1, pos.read(&mut buf).unwrap());
+        assert_eq!((2, 4), (pos.line(), pos.column()), "\\r is in the line");
+        assert_eq!(1, pos.read(&mut buf).unwrap());
+        assert_eq!((3, 1, 7), (pos.line(), pos.column(), pos.line_start()));
+
+        let mut pos = LinePos::new(io::Cursor::new(b"ab\ncd\r\nef\rgh"));
+        read_all(&mut pos);
+        assert_eq!((3, 6, 7), (pos.line(), pos.column(), pos.line_start()));
+        assert_eq!(12, pos.position());
+    }
+
+    #[test]
+    fn any_line_ending() {
+        let mut pos = LinePos::new(ShortRead::new(
+            io::Cursor::new(b"ab\ncd\r\nef\rgh\r\r\n\n"),
+            std::iter::repeat(1),
+        ))
+        .with_line_ending(LineEnding::Any);
+        let mut buf = [0u8; 1];
+        for _ in 0.."ab\ncd\r".len() {
+            pos.read_exact(&mut buf).unwrap();
+        }
+        assert_eq!((3, 1, 6), (pos.line(), pos.column(), pos.line_start()));
+        pos.read_exact(&mut buf).unwrap();
+        assert_eq!(
+            (3, 1, 7),
+            (pos.line(), pos.column(), pos.line_start()),
+            "the \\n of a \\r\\n moves the line start, but isn't a new line"
+        );
+
+        for _ in 0.."ef\r".len() {
+            pos.read_exact(&mut buf).unwrap();
+        }
+        assert_eq!((4, 1, 10), (pos.line(), pos.column(), pos.line_start()));
+
+        read_all(&mut pos);
+        assert_eq!((7, 1, 16), (pos.line(), pos.column(), pos.line_start()));
+    }
+
+    #[test]
+    fn chars() {
+        let mut pos = LinePos::new(io::Cursor::new("£1\nsnow ☃ crab 🦀 end"));
+        let mut buf = [0u8; 3];
+        pos.read_exact(&mut buf).unwrap();
+        assert_eq!((1, 4, 3), (pos.line(), pos.column(), pos.char_column()));
+
+        let mut buf = [0u8; 15];
+        pos.read_exact(&mut buf).unwrap();
+        assert_eq!((2, 15, 13), (pos.line(), pos.column(), pos.char_column()));
+
+        read_all(&mut pos);
+        assert_eq!((2, 23, 18), (pos.line(), pos.column(), pos.char_column()));
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut pos = LinePos::new(io::BufReader::with_capacity(
+            4,
+            io::Cursor::new(b"one\ntwo\nthree\n\nfive"),
+        ));
+        let mut line = String::new();
+        pos.read_line(&mut line).unwrap();
+        assert_eq!((2, 1, 4), (pos.line(), pos.column(), pos.line_start()));
+
+        assert_eq!(b"two\n", pos.fill_buf().unwrap());
+        pos.consume(2);
+        assert_eq!((2, 3), (pos.line(), pos.column()));
+
+        let lines: Vec<String> = (&mut pos).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(vec!["o", "three", "", "five"], lines);
+        assert_eq!((5, 5, 15), (pos.line(), pos.column(), pos.line_start()));
+    }
+
+    #[test]
+    fn read_line_at_eof() {
+        let mut pos = LinePos::new(io::BufReader::new(Stats::new(io::Cursor::new(b"one\n"))));
+        let mut line = String::new();
+        assert_eq!(4, pos.read_line(&mut line).unwrap());
+        assert_eq!(0, pos.read_line(&mut line).unwrap());
+        assert_eq!(
+            2,
+            pos.get_ref().get_ref().reads().calls(),
+            "one read for the line, and one to find the end, but no more"
+        );
+        assert_eq!((2, 1, 4), (pos.line(), pos.column(), pos.line_start()));
+    }
+}
@@ -1,48 +1,422 @@
+use std::error;
+use std::fmt;
 use std::io;
+use std::io::BufRead;
+use std::io::IoSlice;
+use std::io::IoSliceMut;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
-/// Track how many bytes have been read from a stream.
+use crate::progress::Reporter;
+use crate::utf8::append_to_string;
+
+/// Track how many bytes have been read from, or written to, a stream.
 ///
 /// This may not line up with the position in the file in case of IO errors,
 /// this can't be done through the `Read` interface. The `position()` returned will
 /// be just before the error, if inspected immediately after the first error.
-pub struct Pos<R: Read> {
+///
+/// For `Write`, only the bytes the underlying writer reports as accepted are counted,
+/// so the position is accurate even if `write` is short.
+///
+/// For `BufRead`, bytes are counted when they are `consume`d, not when they are
+/// buffered, so `read_line` and friends leave the position just after the line.
+///
+/// Seeking through a `Pos` sets the position to the offset returned by the underlying
+/// stream. To have the position be the offset in the stream from the start, rather
+/// than only after the first seek, construct with `Pos::with_stream_position`.
+///
+/// To be told about progress, rather than polling `position()`, attach a `Reporter`
+/// with `with_progress`. To watch the position from another thread, after the `Pos`
+/// has been moved away, take a `PosHandle` with `handle()`.
+///
+/// With `with_error_positions`, errors from the underlying stream record the
+/// position at which they happened, in a `PosError`.
+pub struct Pos<R> {
     inner: R,
     position: u64,
+    reporter: Option<Reporter>,
+    shared: Option<Arc<AtomicU64>>,
+    error_positions: bool,
 }
 
-impl<R: Read> Pos<R> {
+impl<R> Pos<R> {
     pub fn new(inner: R) -> Self {
-        Pos { inner, position: 0 }
+        Pos {
+            inner,
+            position: 0,
+            reporter: None,
+            shared: None,
+            error_positions: false,
+        }
     }
 
-    /// The number of bytes successfully read from the stream.
+    /// Call the `Reporter` as the position advances. Timing starts now.
+    pub fn with_progress(mut self, mut reporter: Reporter) -> Self {
+        reporter.start(self.position);
+        self.reporter = Some(reporter);
+        self
+    }
+
+    /// Wrap errors from the underlying stream in a `PosError`, recording the position.
+    ///
+    /// The error's kind is preserved. `Interrupted` and `WouldBlock` errors, which are
+    /// expected to be retried, are passed through untouched.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use std::io;
+    /// # use std::io::Read;
+    /// use iowrap::Pos;
+    /// use iowrap::PosError;
+    ///
+    /// # struct Broken;
+    /// # impl Read for Broken {
+    /// #     fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+    /// #         Err(io::ErrorKind::ConnectionReset.into())
+    /// #     }
+    /// # }
+    /// let mut pos = Pos::new(io::Cursor::new(b"hello").chain(Broken)).with_error_positions();
+    /// let err = pos.read_to_end(&mut Vec::new()).unwrap_err();
+    /// assert_eq!(io::ErrorKind::ConnectionReset, err.kind());
+    /// assert_eq!("I/O error at byte 5: connection reset", err.to_string());
+    ///
+    /// let inner = err.get_ref().and_then(|e| e.downcast_ref::<PosError>()).unwrap();
+    /// assert_eq!(5, inner.position());
+    /// ```
+    pub fn with_error_positions(mut self) -> Self {
+        self.error_positions = true;
+        self
+    }
+
+    /// The number of bytes successfully read from, or written to, the stream.
     pub fn position(&self) -> u64 {
         self.position
     }
 
+    /// Remember the current position, to check how much is read (or written) after it.
+    pub fn mark(&self) -> Mark {
+        Mark {
+            start: self.position,
+        }
+    }
+
+    /// A handle which can read the position from elsewhere, e.g. another thread.
+    ///
+    /// All handles share one counter, which is only updated once one has been taken.
+    pub fn handle(&mut self) -> PosHandle {
+        let position = self.position;
+        let shared = self
+            .shared
+            .get_or_insert_with(|| Arc::new(AtomicU64::new(position)));
+        PosHandle {
+            shared: Arc::clone(shared),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading or writing directly through the underlying stream won't be counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    fn advance(&mut self, count: usize) {
+        self.set_position(self.position.saturating_add(count as u64));
+    }
+
+    fn error(&self, e: io::Error) -> io::Error {
+        at_position(self.error_positions, self.position, e)
+    }
+
+    fn reached_end(&mut self) {
+        if let Some(reporter) = &mut self.reporter {
+            reporter.finish(self.position);
+        }
+    }
+
+    fn set_position(&mut self, position: u64) {
+        self.position = position;
+        if let Some(shared) = &self.shared {
+            shared.store(position, Ordering::Relaxed);
+        }
+        if let Some(reporter) = &mut self.reporter {
+            reporter.update(position);
+        }
+    }
+}
+
+impl<R: Seek> Pos<R> {
+    /// Start counting from the underlying stream's current position, e.g. for a file
+    /// which has already been partially read.
+    pub fn with_stream_position(mut inner: R) -> io::Result<Self> {
+        let position = inner.stream_position()?;
+        let mut pos = Pos::new(inner);
+        pos.position = position;
+        Ok(pos)
+    }
+}
+
+/// A position in a `Pos`, from `Pos::mark`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// # use std::io::Read;
+/// use iowrap::Pos;
+///
+/// let mut pos = Pos::new(io::Cursor::new(b"\x03abcdef"));
+/// let mut len = [0u8; 1];
+/// pos.read_exact(&mut len).unwrap();
+/// let record = pos.mark();
+/// pos.read_exact(&mut [0u8; 2]).unwrap();
+/// assert_eq!(2, record.bytes_since(&pos));
+/// assert_eq!(
+///     io::ErrorKind::InvalidData,
+///     record.expect_consumed(&pos, u64::from(len[0])).unwrap_err().kind()
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Mark {
+    start: u64,
+}
+
+impl Mark {
+    /// The `position()` when the mark was made.
+    pub fn position(&self) -> u64 {
+        self.start
+    }
+
+    /// How far the `Pos` has moved since the mark, or zero if it has been seeked back
+    /// before the mark.
+    pub fn bytes_since<R>(&self, pos: &Pos<R>) -> u64 {
+        pos.position().saturating_sub(self.start)
+    }
+
+    /// Check that exactly `len` bytes have been processed since the mark, failing
+    /// with `ErrorKind::InvalidData` if more, or fewer, have.
+    pub fn expect_consumed<R>(&self, pos: &Pos<R>, len: u64) -> io::Result<()> {
+        let consumed = self.bytes_since(pos);
+        if consumed == len {
+            return Ok(());
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "expected to consume {} bytes from {}, but consumed {}",
+                len, self.start, consumed
+            ),
+        ))
+    }
+}
+
+/// The position of a `Pos`, readable without access to the `Pos` itself.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// # use std::io::Read;
+/// use iowrap::Pos;
+///
+/// let mut pos = Pos::new(io::Cursor::new(vec![0u8; 100]));
+/// let handle = pos.handle();
+/// std::thread::spawn(move || io::copy(&mut pos, &mut io::sink()))
+///     .join()
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(100, handle.position());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PosHandle {
+    shared: Arc<AtomicU64>,
+}
+
+impl PosHandle {
+    /// The `position()` of the `Pos`, as of some recent time.
+    pub fn position(&self) -> u64 {
+        self.shared.load(Ordering::Relaxed)
+    }
+}
+
+/// The error payload when an error was returned from the stream under a `Pos`,
+/// with `with_error_positions` enabled.
+///
+/// This is returned inside an `io::Error` of the same kind as the original error.
+#[derive(Debug)]
+pub struct PosError {
+    position: u64,
+    source: io::Error,
+}
+
+impl PosError {
+    /// The `position()` of the `Pos` when the error happened.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The error from the underlying stream.
+    pub fn get_ref(&self) -> &io::Error {
+        &self.source
+    }
+
+    pub fn into_inner(self) -> io::Error {
+        self.source
+    }
+}
+
+impl fmt::Display for PosError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "I/O error at byte {}: {}", self.position, self.source)
+    }
+}
+
+impl error::Error for PosError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+fn at_position(enabled: bool, position: u64, e: io::Error) -> io::Error {
+    match e.kind() {
+        _ if !enabled => e,
+        io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => e,
+        kind => io::Error::new(
+            kind,
+            PosError {
+                position,
+                source: e,
+            },
+        ),
+    }
 }
 
 impl<R: Read> Read for Pos<R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match self.inner.read(buf) {
+            Ok(0) if !buf.is_empty() => {
+                self.reached_end();
+                Ok(0)
+            }
+            Ok(count) => {
+                self.advance(count);
+                Ok(count)
+            }
+            Err(e) => Err(self.error(e)),
+        }
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        match self.inner.read_vectored(bufs) {
+            Ok(0) if bufs.iter().any(|buf| !buf.is_empty()) => {
+                self.reached_end();
+                Ok(0)
+            }
+            Ok(count) => {
+                self.advance(count);
+                Ok(count)
+            }
+            Err(e) => Err(self.error(e)),
+        }
+    }
+
+    /// Everything appended to `buf` is counted, even if there's an error part-way.
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let start = buf.len();
+        let ret = self.inner.read_to_end(buf);
+        self.advance(buf.len() - start);
+        match ret {
             Ok(count) => {
-                self.position = self.position.saturating_add(count as u64);
+                self.reached_end();
                 Ok(count)
             }
-            Err(e) => Err(e),
+            Err(e) => Err(self.error(e)),
         }
     }
+
+    /// Everything read is counted, even if it isn't valid UTF-8, and so isn't appended.
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        append_to_string(buf, |bytes| self.read_to_end(bytes))
+    }
+}
+
+impl<R: BufRead> BufRead for Pos<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let buf = self
+            .inner
+            .fill_buf()
+            .map_err(|e| at_position(self.error_positions, self.position, e))?;
+        if buf.is_empty() {
+            if let Some(reporter) = &mut self.reporter {
+                reporter.finish(self.position);
+            }
+        }
+        Ok(buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+        self.advance(amt);
+    }
+}
+
+impl<W: Write> Write for Pos<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = self.inner.write(buf).map_err(|e| self.error(e))?;
+        self.advance(count);
+        Ok(count)
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let count = self.inner.write_vectored(bufs).map_err(|e| self.error(e))?;
+        self.advance(count);
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush().map_err(|e| self.error(e))
+    }
+}
+
+impl<R: Seek> Seek for Pos<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = self.inner.seek(pos).map_err(|e| self.error(e))?;
+        self.set_position(position);
+        Ok(position)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Pos;
+    use super::PosError;
+    use super::PosHandle;
+    use crate::progress::Reporter;
+    use crate::short::ShortRead;
     use std::io;
+    use std::io::BufRead;
+    use std::io::IoSlice;
+    use std::io::IoSliceMut;
     use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::sync::Mutex;
 
     #[test]
     fn smoke_cursor() {
@@ -60,4 +434,375 @@ mod tests {
         assert_eq!(0, pos.read(&mut buf).unwrap());
         assert_eq!(6, pos.position());
     }
+
+    /// Accept at most `limit` bytes per `write` call.
+    struct ShortWrite {
+        inner: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for ShortWrite {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let count = buf.len().min(self.limit);
+            self.inner.extend_from_slice(&buf[..count]);
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write() {
+        let mut pos = Pos::new(ShortWrite {
+            inner: Vec::new(),
+            limit: 3,
+        });
+        assert_eq!(3, pos.write(b"hello").unwrap());
+        assert_eq!(3, pos.position());
+
+        pos.write_all(b"lo world").unwrap();
+        assert_eq!(11, pos.position());
+
+        assert_eq!(
+            2,
+            pos.write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"cd")])
+                .unwrap(),
+            "the default write_vectored only writes the first buffer"
+        );
+        assert_eq!(13, pos.position());
+
+        write!(pos, "cd{}", 12345).unwrap();
+        assert_eq!(20, pos.position());
+        pos.flush().unwrap();
+        assert_eq!(b"hello worldabcd12345", pos.into_inner().inner.as_slice());
+    }
+
+    #[test]
+    fn vectored_to_vec() {
+        let mut pos = Pos::new(Vec::new());
+        assert_eq!(
+            4,
+            pos.write_vectored(&[IoSlice::new(b"ab"), IoSlice::new(b"cd")])
+                .unwrap()
+        );
+        assert_eq!(4, pos.position());
+        assert_eq!(b"abcd", pos.get_ref().as_slice());
+    }
+
+    #[test]
+    fn short_read() {
+        let mut pos = Pos::new(ShortRead::new(
+            io::Cursor::new(b"hello"),
+            vec![2, 0, 3].into_iter(),
+        ));
+        let mut buf = [0u8; 5];
+        assert_eq!(2, pos.read(&mut buf).unwrap());
+        assert_eq!(
+            io::ErrorKind::Interrupted,
+            pos.read(&mut buf).unwrap_err().kind()
+        );
+        assert_eq!(2, pos.position());
+        assert_eq!(3, pos.read(&mut buf).unwrap());
+        assert_eq!(5, pos.position());
+    }
+
+    #[test]
+    fn seek() {
+        let mut pos = Pos::new(io::Cursor::new(vec![7, 8, 9, 10, 11, 12]));
+        assert_eq!(4, pos.seek(SeekFrom::End(-2)).unwrap());
+        assert_eq!(4, pos.position());
+        let mut buf = [0u8; 1];
+        pos.read_exact(&mut buf).unwrap();
+        assert_eq!(11, buf[0]);
+        assert_eq!(5, pos.position());
+
+        assert_eq!(2, pos.seek(SeekFrom::Current(-3)).unwrap());
+        assert_eq!(2, pos.position());
+        pos.write_all(&[0, 0]).unwrap();
+        assert_eq!(4, pos.position());
+        assert_eq!(4, pos.stream_position().unwrap());
+        assert_eq!(&[7, 8, 0, 0, 11, 12], pos.get_ref().get_ref().as_slice());
+
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            pos.seek(SeekFrom::Current(-10)).unwrap_err().kind()
+        );
+        assert_eq!(4, pos.position(), "failed seeks don't move");
+    }
+
+    #[test]
+    fn with_stream_position() {
+        let mut cursor = io::Cursor::new(vec![7, 8, 9, 10, 11, 12]);
+        cursor.set_position(3);
+        let mut pos = Pos::with_stream_position(cursor).unwrap();
+        assert_eq!(3, pos.position());
+        let mut buf = [0u8; 2];
+        pos.read_exact(&mut buf).unwrap();
+        assert_eq!(&[10, 11], &buf);
+        assert_eq!(5, pos.position());
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut pos = Pos::new(io::BufReader::new(ShortRead::new(
+            io::Cursor::new(b"hello\nthere\nworld"),
+            vec![3, 0, 4, 99].into_iter(),
+        )));
+        assert_eq!(b"hel", pos.fill_buf().unwrap());
+        assert_eq!(0, pos.position(), "buffering isn't consuming");
+
+        let mut line = String::new();
+        pos.read_line(&mut line).unwrap();
+        assert_eq!("hello\n", line);
+        assert_eq!(6, pos.position());
+
+        let lines: Vec<String> = (&mut pos).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(vec!["there", "world"], lines);
+        assert_eq!(17, pos.position());
+    }
+
+    fn recorder() -> (Arc<Mutex<Vec<u64>>>, Reporter) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let reporter = Reporter::new({
+            let seen = Arc::clone(&seen);
+            move |progress| seen.lock().unwrap().push(progress.position())
+        });
+        (seen, reporter)
+    }
+
+    #[test]
+    fn progress() {
+        let (seen, reporter) = recorder();
+        let mut pos = Pos::new(ShortRead::new(
+            io::Cursor::new(vec![0u8; 10]),
+            vec![3, 0, 3, 3, 99].into_iter(),
+        ))
+        .with_progress(reporter);
+        io::copy(&mut pos, &mut io::sink()).unwrap();
+        assert_eq!(vec![3, 6, 9, 10], *seen.lock().unwrap());
+
+        let (seen, reporter) = recorder();
+        let mut pos = Pos::new(ShortRead::new(
+            io::Cursor::new(vec![0u8; 10]),
+            std::iter::repeat(3),
+        ))
+        .with_progress(reporter.every_bytes(5));
+        io::copy(&mut pos, &mut io::sink()).unwrap();
+        assert_eq!(vec![6, 10], *seen.lock().unwrap(), "throttled, then at eof");
+    }
+
+    #[test]
+    fn progress_total_and_seek() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let reporter = Reporter::new({
+            let seen = Arc::clone(&seen);
+            move |progress| seen.lock().unwrap().push(progress.percent().unwrap())
+        })
+        .total(8)
+        .every_bytes(4);
+
+        let mut pos = Pos::new(io::Cursor::new(vec![0u8; 8])).with_progress(reporter);
+        pos.read_exact(&mut [0u8; 4]).unwrap();
+        pos.seek(SeekFrom::Start(2)).unwrap();
+        pos.read_exact(&mut [0u8; 4]).unwrap();
+        pos.write_all(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(vec![50., 75., 100.], *seen.lock().unwrap());
+    }
+
+    #[test]
+    fn progress_buf_read() {
+        let (seen, reporter) = recorder();
+        let mut pos =
+            Pos::new(io::Cursor::new(b"one\ntwo\nthree")).with_progress(reporter.every_bytes(8));
+        assert_eq!(3, (&mut pos).lines().count());
+        assert_eq!(vec![8, 13], *seen.lock().unwrap());
+    }
+
+    /// Fail every call.
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::ConnectionReset.into())
+        }
+    }
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+    }
+
+    fn pos_error(e: &io::Error) -> Option<&PosError> {
+        e.get_ref().and_then(|e| e.downcast_ref::<PosError>())
+    }
+
+    #[test]
+    fn error_positions() {
+        let mut pos = Pos::new(io::Cursor::new(b"hello").chain(Broken));
+        let e = pos.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::ConnectionReset, e.kind());
+        assert!(pos_error(&e).is_none(), "off by default");
+
+        let mut pos = Pos::new(io::Cursor::new(b"hello").chain(Broken)).with_error_positions();
+        let e = pos.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(io::ErrorKind::ConnectionReset, e.kind());
+        let inner = pos_error(&e).expect("typed error");
+        assert_eq!(5, inner.position());
+        assert_eq!(io::ErrorKind::ConnectionReset, inner.get_ref().kind());
+        assert!(std::error::Error::source(inner).is_some());
+
+        let mut pos = Pos::new(ShortRead::new(
+            io::Cursor::new(b"hello"),
+            vec![2, 0].into_iter(),
+        ))
+        .with_error_positions();
+        pos.read_exact(&mut [0u8; 2]).unwrap();
+        let e = pos.read(&mut [0u8; 2]).unwrap_err();
+        assert_eq!(io::ErrorKind::Interrupted, e.kind());
+        assert!(pos_error(&e).is_none(), "interruptions aren't wrapped");
+    }
+
+    #[test]
+    fn error_positions_buf_read_and_write() {
+        let mut pos = Pos::new(io::BufReader::with_capacity(
+            4,
+            io::Cursor::new(b"one\ntwo\n").chain(Broken),
+        ))
+        .with_error_positions();
+        let e = (&mut pos).lines().find_map(|l| l.err()).unwrap();
+        assert_eq!(Some(8), pos_error(&e).map(|e| e.position()));
+
+        let mut pos = Pos::new(io::Cursor::new(b"ab").chain(Broken)).with_error_positions();
+        let mut buf = [0u8; 4];
+        let e = pos.read_exact(&mut buf).unwrap_err();
+        assert_eq!(Some(2), pos_error(&e).map(|e| e.position()));
+
+        let mut pos = Pos::new(Broken).with_error_positions();
+        let e = pos.write_all(b"hello").unwrap_err();
+        assert_eq!(io::ErrorKind::BrokenPipe, e.kind());
+        assert_eq!(Some(0), pos_error(&e).map(|e| e.position()));
+        assert!(pos_error(&pos.flush().unwrap_err()).is_some());
+
+        let mut pos = Pos::new(io::Cursor::new(b"ab")).with_error_positions();
+        pos.read_exact(&mut buf[..2]).unwrap();
+        let e = pos.seek(SeekFrom::Current(-3)).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, e.kind());
+        assert_eq!(Some(2), pos_error(&e).map(|e| e.position()));
+    }
+
+    #[test]
+    fn whole_reads() {
+        let mut pos = Pos::new(io::Cursor::new(b"hello world"));
+        let (mut a, mut b) = ([0u8; 2], [0u8; 3]);
+        assert_eq!(
+            5,
+            pos.read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "the default read_vectored would only fill the first buffer"
+        );
+        assert_eq!((b"he", b"llo"), (&a, &b));
+        assert_eq!(5, pos.position());
+
+        let mut buf = b"!".to_vec();
+        assert_eq!(6, pos.read_to_end(&mut buf).unwrap());
+        assert_eq!(b"! world", buf.as_slice());
+        assert_eq!(11, pos.position());
+
+        let mut pos = Pos::new(io::Cursor::new(b"caf\xc3\xa9"));
+        let mut s = String::from("a ");
+        assert_eq!(5, pos.read_to_string(&mut s).unwrap());
+        assert_eq!("a café", s);
+        assert_eq!(5, pos.position());
+
+        let mut pos = Pos::new(io::Cursor::new(b"caf\xc3"));
+        let mut s = String::from("a ");
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            pos.read_to_string(&mut s).unwrap_err().kind()
+        );
+        assert_eq!("a ", s, "invalid data isn't appended");
+        assert_eq!(4, pos.position(), "but was read");
+
+        let mut pos = Pos::new(io::Cursor::new(b"hello").chain(Broken)).with_error_positions();
+        let mut buf = Vec::new();
+        let e = pos.read_to_end(&mut buf).unwrap_err();
+        assert_eq!(5, buf.len());
+        assert_eq!(5, pos.position());
+        assert_eq!(Some(5), pos_error(&e).map(|e| e.position()));
+    }
+
+    #[test]
+    fn marks() {
+        let mut pos = Pos::new(io::Cursor::new(vec![7, 8, 9, 10, 11, 12]));
+        pos.read_exact(&mut [0u8; 1]).unwrap();
+        let outer = pos.mark();
+        assert_eq!(1, outer.position());
+        assert_eq!(0, outer.bytes_since(&pos));
+        outer.expect_consumed(&pos, 0).unwrap();
+
+        pos.read_exact(&mut [0u8; 2]).unwrap();
+        let inner = pos.mark();
+        pos.read_exact(&mut [0u8; 2]).unwrap();
+        inner.expect_consumed(&pos, 2).unwrap();
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            inner.expect_consumed(&pos, 3).unwrap_err().kind(),
+            "under-read"
+        );
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            outer.expect_consumed(&pos, 3).unwrap_err().kind(),
+            "over-read"
+        );
+        outer.expect_consumed(&pos, 4).unwrap();
+
+        pos.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(0, outer.bytes_since(&pos), "seeked back before the mark");
+    }
+
+    #[test]
+    fn auto_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Pos<io::Cursor<Vec<u8>>>>();
+        assert_send_sync::<Reporter>();
+        assert_send_sync::<PosHandle>();
+
+        // the callback itself needn't be Sync
+        let count = std::cell::Cell::new(0);
+        let reporter = Reporter::new(move |_| count.set(count.get() + 1));
+        let _: &dyn Sync = &Pos::new(io::empty()).with_progress(reporter);
+    }
+
+    #[test]
+    fn handle() {
+        let mut cursor = io::Cursor::new(vec![7, 8, 9, 10, 11, 12]);
+        cursor.set_position(1);
+        let mut pos = Pos::with_stream_position(cursor).unwrap();
+        pos.read_exact(&mut [0u8; 2]).unwrap();
+
+        let handle = pos.handle();
+        assert_eq!(3, handle.position(), "starts from where the Pos is");
+        let other = handle.clone();
+
+        pos.read_exact(&mut [0u8; 2]).unwrap();
+        assert_eq!((5, 5), (handle.position(), other.position()));
+        pos.seek(SeekFrom::Start(0)).unwrap();
+        assert_eq!(0, handle.position());
+
+        let pos = std::thread::spawn(move || {
+            pos.write_all(b"hello").unwrap();
+            pos
+        })
+        .join()
+        .unwrap();
+        assert_eq!(5, handle.position());
+        assert_eq!(pos.position(), handle.position());
+    }
 }
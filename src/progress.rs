@@ -0,0 +1,240 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+/// How far through a stream we are, as passed to a `Reporter`'s callback.
+#[derive(Copy, Clone, Debug)]
+pub struct Progress {
+    position: u64,
+    start_position: u64,
+    total: Option<u64>,
+    elapsed: Duration,
+}
+
+impl Progress {
+    /// The `position()` of the `Pos`.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The expected final position, if it was provided to the `Reporter`.
+    pub fn total(&self) -> Option<u64> {
+        self.total
+    }
+
+    /// The time since the `Reporter` was attached.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// How far through the `total` we are, from `0.0` to `100.0`.
+    pub fn percent(&self) -> Option<f64> {
+        let total = self.total?;
+        if 0 == total {
+            return Some(100.);
+        }
+        Some((self.position as f64 / total as f64 * 100.).min(100.))
+    }
+
+    /// The average rate since the `Reporter` was attached.
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        if secs <= 0. {
+            return None;
+        }
+        Some(self.position.saturating_sub(self.start_position) as f64 / secs)
+    }
+
+    /// How much longer it will take to reach the `total`, at the average rate so far.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.total?.saturating_sub(self.position);
+        if 0 == remaining {
+            return Some(Duration::ZERO);
+        }
+        let rate = self.bytes_per_second()?;
+        if rate <= 0. {
+            return None;
+        }
+        Duration::try_from_secs_f64(remaining as f64 / rate).ok()
+    }
+}
+
+type Callback = Box<dyn FnMut(&Progress) + Send>;
+
+/// Call a function, now and then, as a `Pos` advances.
+///
+/// By default, the callback is called every time the position changes. This can be
+/// throttled with `every_bytes` and/or `every`; if both are set, either will trigger
+/// a report. A final report is also made when a read hits end-of-file.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use std::time::Duration;
+/// use iowrap::Pos;
+/// use iowrap::Reporter;
+///
+/// let reporter = Reporter::new(|progress| {
+///     eprintln!(
+///         "{:.1}%, eta: {:?}",
+///         progress.percent().unwrap_or(0.),
+///         progress.eta()
+///     )
+/// })
+/// .every_bytes(1024 * 1024)
+/// .every(Duration::from_secs(1))
+/// .total(100);
+///
+/// let mut pos = Pos::new(io::Cursor::new(vec![0u8; 100])).with_progress(reporter);
+/// io::copy(&mut pos, &mut io::sink()).unwrap();
+/// ```
+pub struct Reporter {
+    /// Only ever accessed through `get_mut`; the `Mutex` is just so `Reporter`,
+    /// and so `Pos`, is `Sync` without requiring the callback to be.
+    callback: Mutex<Callback>,
+    every_bytes: Option<u64>,
+    every: Option<Duration>,
+    total: Option<u64>,
+    start: Instant,
+    start_position: u64,
+    last_position: u64,
+    last_time: Instant,
+}
+
+impl Reporter {
+    pub fn new<F: FnMut(&Progress) + Send + 'static>(callback: F) -> Self {
+        let now = Instant::now();
+        Reporter {
+            callback: Mutex::new(Box::new(callback)),
+            every_bytes: None,
+            every: None,
+            total: None,
+            start: now,
+            start_position: 0,
+            last_position: 0,
+            last_time: now,
+        }
+    }
+
+    /// Report only after at least `bytes` more have been processed.
+    pub fn every_bytes(mut self, bytes: u64) -> Self {
+        self.every_bytes = Some(bytes);
+        self
+    }
+
+    /// Report only after at least `interval` has passed.
+    pub fn every(mut self, interval: Duration) -> Self {
+        self.every = Some(interval);
+        self
+    }
+
+    /// The expected size of the stream, for `Progress::percent` and `Progress::eta`.
+    pub fn total(mut self, total: u64) -> Self {
+        self.total = Some(total);
+        self
+    }
+
+    /// Start timing, and counting, from here.
+    pub(crate) fn start(&mut self, position: u64) {
+        self.start = Instant::now();
+        self.last_time = self.start;
+        self.start_position = position;
+        self.last_position = position;
+    }
+
+    pub(crate) fn update(&mut self, position: u64) {
+        if position < self.last_position {
+            // we've been seeked backwards; measure from here
+            self.last_position = position;
+        }
+
+        let moved = position - self.last_position;
+        if 0 == moved {
+            return;
+        }
+
+        let due = match (self.every_bytes, self.every) {
+            (None, None) => true,
+            (bytes, interval) => {
+                bytes.is_some_and(|bytes| moved >= bytes)
+                    || interval.is_some_and(|interval| self.last_time.elapsed() >= interval)
+            }
+        };
+
+        if due {
+            self.report(position);
+        }
+    }
+
+    /// Report now, unless we already have at this position.
+    pub(crate) fn finish(&mut self, position: u64) {
+        if position != self.last_position {
+            self.report(position);
+        }
+    }
+
+    fn report(&mut self, position: u64) {
+        let now = Instant::now();
+        self.last_position = position;
+        self.last_time = now;
+        let callback = self
+            .callback
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        callback(&Progress {
+            position,
+            start_position: self.start_position,
+            total: self.total,
+            elapsed: now - self.start,
+        });
+    }
+}
+
+impl fmt::Debug for Reporter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Reporter")
+            .field("every_bytes", &self.every_bytes)
+            .field("every", &self.every)
+            .field("total", &self.total)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Progress;
+
+    fn progress(position: u64, total: Option<u64>, elapsed: Duration) -> Progress {
+        Progress {
+            position,
+            start_position: 0,
+            total,
+            elapsed,
+        }
+    }
+
+    #[test]
+    fn maths() {
+        let p = progress(25, Some(100), Duration::from_secs(5));
+        assert_eq!(Some(25.), p.percent());
+        assert_eq!(Some(5.), p.bytes_per_second());
+        assert_eq!(Some(Duration::from_secs(15)), p.eta());
+
+        let p = progress(25, None, Duration::ZERO);
+        assert_eq!(None, p.percent());
+        assert_eq!(None, p.bytes_per_second());
+        assert_eq!(None, p.eta());
+
+        let p = progress(0, Some(100), Duration::from_secs(5));
+        assert_eq!(Some(0.), p.percent());
+        assert_eq!(None, p.eta(), "no progress, no idea");
+
+        let p = progress(200, Some(100), Duration::from_secs(5));
+        assert_eq!(Some(100.), p.percent());
+        assert_eq!(Some(Duration::ZERO), p.eta());
+    }
+}
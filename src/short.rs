@@ -1,4 +1,5 @@
 use std::io;
+use std::io::IoSliceMut;
 use std::io::Read;
 
 /// Intentionally return short reads, to test `Read` code.
@@ -11,6 +12,9 @@ use std::io::Read;
 /// Currently, no effort is made to make reads longer, if the underlying
 /// reader naturally returns short reads.
 ///
+/// `read_vectored` is limited in the same way, across all the buffers. `read_to_end`
+/// and friends aren't forwarded, so go through the limited `read`.
+///
 /// # Examples
 ///
 /// Short read:
@@ -58,6 +62,25 @@ impl<R: Read, I: Iterator<Item = usize>> Read for ShortRead<R, I> {
         let buf = &mut buf[..wanted];
         self.inner.read(buf)
     }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let mut wanted = match self.decider.next() {
+            Some(0) => return Err(io::Error::from(io::ErrorKind::Interrupted)),
+            Some(wanted) => wanted,
+            None => return Ok(0),
+        };
+
+        let mut limited = Vec::with_capacity(bufs.len());
+        for buf in bufs.iter_mut() {
+            if 0 == wanted {
+                break;
+            }
+            let len = wanted.min(buf.len());
+            limited.push(IoSliceMut::new(&mut buf[..len]));
+            wanted -= len;
+        }
+        self.inner.read_vectored(&mut limited)
+    }
 }
 
 impl<R: Read, I: Iterator<Item = usize>> ShortRead<R, I> {
@@ -74,6 +97,7 @@ impl<R: Read, I: Iterator<Item = usize>> ShortRead<R, I> {
 mod tests {
     use crate::short::ShortRead;
     use std::io;
+    use std::io::IoSliceMut;
     use std::io::Read;
 
     #[test]
@@ -113,6 +137,39 @@ mod tests {
         assert_eq!(1, interrupting.read(&mut buf).unwrap());
     }
 
+    #[test]
+    fn vectored() {
+        let mut naughty = ShortRead::new(io::Cursor::new(b"1234567890"), vec![3, 0, 9].into_iter());
+        let (mut a, mut b) = ([0u8; 2], [0u8; 2]);
+        assert_eq!(
+            3,
+            naughty
+                .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap()
+        );
+        assert_eq!((&b"12"[..], &b"3"[..]), (&a[..], &b[..1]));
+        assert_eq!(
+            io::ErrorKind::Interrupted,
+            naughty
+                .read_vectored(&mut [IoSliceMut::new(&mut a)])
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            4,
+            naughty
+                .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap()
+        );
+        assert_eq!((b"45", b"67"), (&a, &b));
+        assert_eq!(
+            0,
+            naughty
+                .read_vectored(&mut [IoSliceMut::new(&mut a)])
+                .unwrap()
+        );
+    }
+
     #[test]
     fn std_bufreader_leaves_us_alone() {
         use std::io::BufRead;
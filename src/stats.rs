@@ -0,0 +1,309 @@
+use std::fmt;
+use std::io;
+use std::io::IoSlice;
+use std::io::IoSliceMut;
+use std::io::Read;
+use std::io::Write;
+use std::time::Duration;
+use std::time::Instant;
+
+/// Record statistics about the calls made to a stream, e.g. to find out why a
+/// reader is slow.
+///
+/// Every `read`, `read_vectored`, `write` and `write_vectored` call is timed, and its result recorded,
+/// in a `CallStats` for the direction.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// # use std::io::Read;
+/// use iowrap::ShortRead;
+/// use iowrap::Stats;
+///
+/// let mut stats = Stats::new(ShortRead::new(
+///     io::Cursor::new(b"hello world"),
+///     vec![1, 0, 1, 9].into_iter(),
+/// ));
+/// io::copy(&mut stats, &mut io::sink()).unwrap();
+/// let reads = stats.reads();
+/// assert_eq!((4, 11, 1), (reads.calls(), reads.bytes(), reads.interrupted()));
+/// assert_eq!((Some(0), Some(9)), (reads.min(), reads.max()));
+/// ```
+pub struct Stats<R> {
+    inner: R,
+    reads: CallStats,
+    writes: CallStats,
+}
+
+impl<R> Stats<R> {
+    pub fn new(inner: R) -> Self {
+        Stats {
+            inner,
+            reads: CallStats::default(),
+            writes: CallStats::default(),
+        }
+    }
+
+    /// Statistics for `read` and `read_vectored` calls.
+    pub fn reads(&self) -> &CallStats {
+        &self.reads
+    }
+
+    /// Statistics for `write` and `write_vectored` calls.
+    pub fn writes(&self) -> &CallStats {
+        &self.writes
+    }
+
+    /// Forget everything recorded so far.
+    pub fn reset(&mut self) {
+        self.reads = CallStats::default();
+        self.writes = CallStats::default();
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Calls made directly on the underlying stream won't be recorded.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+/// What happened to calls in one direction, through a `Stats`.
+///
+/// Successful calls, including those returning zero bytes, are counted in `calls()`,
+/// and their sizes go into the `min()`, `max()`, `mean()` and `histogram()`. Errors are
+/// counted separately, but are timed like every other call.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CallStats {
+    calls: u64,
+    bytes: u64,
+    min: Option<usize>,
+    max: Option<usize>,
+    interrupted: u64,
+    would_block: u64,
+    errors: u64,
+    time: Duration,
+    histogram: Vec<u64>,
+}
+
+impl CallStats {
+    /// The number of successful calls.
+    pub fn calls(&self) -> u64 {
+        self.calls
+    }
+
+    /// The total number of bytes transferred.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// The fewest bytes transferred by a successful call.
+    pub fn min(&self) -> Option<usize> {
+        self.min
+    }
+
+    /// The most bytes transferred by a successful call.
+    pub fn max(&self) -> Option<usize> {
+        self.max
+    }
+
+    /// The average number of bytes transferred by a successful call.
+    pub fn mean(&self) -> Option<f64> {
+        if 0 == self.calls {
+            return None;
+        }
+        Some(self.bytes as f64 / self.calls as f64)
+    }
+
+    /// The number of calls which failed with `ErrorKind::Interrupted`.
+    pub fn interrupted(&self) -> u64 {
+        self.interrupted
+    }
+
+    /// The number of calls which failed with `ErrorKind::WouldBlock`.
+    pub fn would_block(&self) -> u64 {
+        self.would_block
+    }
+
+    /// The number of calls which failed with any other error.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// The total time spent inside the underlying stream.
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Bytes per second, while inside the underlying stream.
+    pub fn throughput(&self) -> Option<f64> {
+        let secs = self.time.as_secs_f64();
+        if secs <= 0. {
+            return None;
+        }
+        Some(self.bytes as f64 / secs)
+    }
+
+    /// The number of successful calls by size, in power-of-two buckets.
+    ///
+    /// Bucket `0` counts calls which returned zero bytes, and bucket `n` counts calls
+    /// which returned from `2^(n-1)` to `2^n - 1` bytes, so a run of `1` byte reads
+    /// shows up in bucket `1`, and `8KiB` reads in bucket `14`.
+    pub fn histogram(&self) -> &[u64] {
+        &self.histogram
+    }
+
+    fn record(&mut self, started: Instant, result: &io::Result<usize>) {
+        self.time += started.elapsed();
+        let count = match result {
+            Ok(count) => *count,
+            Err(e) => {
+                match e.kind() {
+                    io::ErrorKind::Interrupted => self.interrupted += 1,
+                    io::ErrorKind::WouldBlock => self.would_block += 1,
+                    _ => self.errors += 1,
+                }
+                return;
+            }
+        };
+
+        self.calls += 1;
+        self.bytes = self.bytes.saturating_add(count as u64);
+        self.min = Some(self.min.map_or(count, |min| min.min(count)));
+        self.max = Some(self.max.map_or(count, |max| max.max(count)));
+
+        let bucket = (usize::BITS - count.leading_zeros()) as usize;
+        if self.histogram.len() <= bucket {
+            self.histogram.resize(bucket + 1, 0);
+        }
+        self.histogram[bucket] += 1;
+    }
+}
+
+impl fmt::Display for CallStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} calls, {} bytes", self.calls, self.bytes)?;
+        if let (Some(min), Some(mean), Some(max)) = (self.min, self.mean(), self.max) {
+            write!(f, " (min {}, mean {:.1}, max {})", min, mean, max)?;
+        }
+        write!(
+            f,
+            ", {} interrupted, {} would block, {} errors, in {:?}",
+            self.interrupted, self.would_block, self.errors, self.time
+        )
+    }
+}
+
+impl<R: Read> Read for Stats<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let started = Instant::now();
+        let result = self.inner.read(buf);
+        self.reads.record(started, &result);
+        result
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        let started = Instant::now();
+        let result = self.inner.read_vectored(bufs);
+        self.reads.record(started, &result);
+        result
+    }
+}
+
+impl<W: Write> Write for Stats<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let started = Instant::now();
+        let result = self.inner.write(buf);
+        self.writes.record(started, &result);
+        result
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let started = Instant::now();
+        let result = self.inner.write_vectored(bufs);
+        self.writes.record(started, &result);
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::IoSliceMut;
+    use std::io::Read;
+    use std::io::Write;
+
+    use crate::ShortRead;
+
+    use super::Stats;
+
+    #[test]
+    fn reads() {
+        let mut stats = Stats::new(ShortRead::new(
+            io::Cursor::new(vec![0u8; 20]),
+            vec![1, 1, 0, 3, 0, 8, 7].into_iter(),
+        ));
+        assert_eq!(20, io::copy(&mut stats, &mut io::sink()).unwrap());
+
+        let reads = stats.reads();
+        assert_eq!(6, reads.calls(), "five reads, and eof");
+        assert_eq!(20, reads.bytes());
+        assert_eq!(Some(0), reads.min());
+        assert_eq!(Some(8), reads.max());
+        assert_eq!(Some(20. / 6.), reads.mean());
+        assert_eq!(
+            (2, 0, 0),
+            (reads.interrupted(), reads.would_block(), reads.errors())
+        );
+        assert_eq!(&[1, 2, 1, 1, 1], reads.histogram());
+        assert_eq!(0, stats.writes().calls());
+
+        stats.reset();
+        assert_eq!(0, stats.reads().calls());
+        assert_eq!(None, stats.reads().mean());
+        assert!(stats.reads().histogram().is_empty());
+    }
+
+    #[test]
+    fn read_vectored() {
+        let mut stats = Stats::new(io::Cursor::new(b"hello world"));
+        let (mut a, mut b) = ([0u8; 4], [0u8; 4]);
+        assert_eq!(
+            8,
+            stats
+                .read_vectored(&mut [IoSliceMut::new(&mut a), IoSliceMut::new(&mut b)])
+                .unwrap(),
+            "not downgraded to a single buffer read"
+        );
+        assert_eq!((1, 8), (stats.reads().calls(), stats.reads().bytes()));
+        assert_eq!(Some(8), stats.reads().max());
+    }
+
+    #[test]
+    fn writes() {
+        let mut stats = Stats::new(io::Cursor::new([0u8; 4]));
+        stats.write_all(b"ab").unwrap();
+        assert_eq!(
+            io::ErrorKind::WriteZero,
+            stats.write_all(b"cdef").unwrap_err().kind()
+        );
+
+        let writes = stats.writes();
+        assert_eq!((3, 4, 0), (writes.calls(), writes.bytes(), writes.errors()));
+        assert_eq!(&[1, 0, 2], writes.histogram());
+        assert!(writes
+            .to_string()
+            .starts_with("3 calls, 4 bytes (min 0, mean 1.3, max 2), 0 interrupted"));
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::io;
+use std::io::Read;
+
+use crate::var_buf::CapacityExceeded;
+
+/// The default limit on how many bytes can be pushed back at once.
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// Push bytes back onto the front of a stream, like C's `ungetc`.
+///
+/// Pushed back bytes are returned by the next `read`s, before any more of the
+/// underlying stream. They needn't be the bytes which were read.
+///
+/// The number of bytes which can be held at once is limited, by default to 1KiB;
+/// pushing back more fails with a `CapacityExceeded`, and pushes back nothing.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// # use std::io::Read;
+/// use iowrap::Unread;
+///
+/// let mut input = Unread::new(io::Cursor::new(b"123+456"));
+/// let mut number = Vec::new();
+/// let mut byte = [0u8; 1];
+/// while input.read(&mut byte).unwrap() == 1 {
+///     if !byte[0].is_ascii_digit() {
+///         // oops, read one too many
+///         input.unread(&byte).unwrap();
+///         break;
+///     }
+///     number.push(byte[0]);
+/// }
+/// assert_eq!(b"123", number.as_slice());
+///
+/// let mut rest = String::new();
+/// input.read_to_string(&mut rest).unwrap();
+/// assert_eq!("+456", rest);
+/// ```
+pub struct Unread<R> {
+    inner: R,
+    pushed: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl<R> Unread<R> {
+    pub fn new(inner: R) -> Self {
+        Unread {
+            inner,
+            pushed: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Allow up to `capacity` bytes to be pushed back at once.
+    ///
+    /// # Panics
+    ///
+    /// If more than `capacity` bytes are already pushed back.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(
+            self.pushed.len() <= capacity,
+            "{} bytes are already pushed back",
+            self.pushed.len()
+        );
+        self.capacity = capacity;
+        self
+    }
+
+    /// Push `bytes` back, so they are read next, in order, before anything
+    /// pushed back previously.
+    pub fn unread(&mut self, bytes: &[u8]) -> Result<(), CapacityExceeded> {
+        let requested = self.pushed.len() + bytes.len();
+        if requested > self.capacity {
+            return Err(CapacityExceeded::new(requested, self.capacity));
+        }
+
+        for &b in bytes.iter().rev() {
+            self.pushed.push_front(b);
+        }
+        Ok(())
+    }
+
+    /// Push a single byte back, so it is read next.
+    pub fn unread_byte(&mut self, byte: u8) -> Result<(), CapacityExceeded> {
+        self.unread(&[byte])
+    }
+
+    /// The number of pushed back bytes which haven't been read again yet.
+    pub fn pending(&self) -> usize {
+        self.pushed.len()
+    }
+
+    /// The most bytes which can be pushed back at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Any pushed back bytes are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for Unread<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.pushed.is_empty() {
+            return self.pushed.read(buf);
+        }
+
+        self.inner.read(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Read;
+
+    use crate::ShortRead;
+
+    use super::Unread;
+
+    #[test]
+    fn unread() {
+        let mut input = Unread::new(ShortRead::new(
+            io::Cursor::new(b"hello world"),
+            vec![3, 0, 99].into_iter(),
+        ));
+        let mut buf = [0u8; 5];
+        assert_eq!(3, input.read(&mut buf).unwrap());
+        assert_eq!(b"hel", &buf[..3]);
+
+        input.unread(b"el").unwrap();
+        input.unread_byte(b'j').unwrap();
+        assert_eq!(3, input.pending());
+        assert_eq!(
+            3,
+            input.read(&mut buf).unwrap(),
+            "only the pushed back bytes"
+        );
+        assert_eq!(b"jel", &buf[..3]);
+        assert_eq!(0, input.pending());
+
+        assert_eq!(
+            io::ErrorKind::Interrupted,
+            input.read(&mut buf).unwrap_err().kind()
+        );
+        input.unread(b"").unwrap();
+        let mut rest = String::new();
+        input.read_to_string(&mut rest).unwrap();
+        assert_eq!("lo world", rest);
+
+        input.unread(b"!").unwrap();
+        assert_eq!(1, input.read(&mut buf).unwrap());
+        assert_eq!(b'!', buf[0]);
+        assert_eq!(0, input.read(&mut buf).unwrap());
+    }
+
+    #[test]
+    fn capacity() {
+        let mut input = Unread::new(io::Cursor::new(b"world")).with_capacity(4);
+        assert_eq!(4, input.capacity());
+        input.unread(b"lo ").unwrap();
+
+        let e = input.unread(b"el").unwrap_err();
+        assert_eq!((5, 4), (e.requested(), e.limit()));
+        assert_eq!(3, input.pending(), "nothing more was pushed back");
+        assert_eq!(
+            io::ErrorKind::OutOfMemory,
+            io::Error::from(input.unread(b"hel").unwrap_err()).kind()
+        );
+
+        input.unread_byte(b'l').unwrap();
+        let mut all = Vec::new();
+        input.read_to_end(&mut all).unwrap();
+        assert_eq!(b"llo world", all.as_slice());
+    }
+
+    #[test]
+    #[should_panic]
+    fn shrink_below_pending() {
+        let mut input = Unread::new(io::empty());
+        input.unread(b"hello").unwrap();
+        let _ = input.with_capacity(4);
+    }
+}
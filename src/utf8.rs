@@ -0,0 +1,34 @@
+use std::io;
+use std::str;
+
+/// Implement `Read::read_to_string` in terms of a `read_to_end`, with the same
+/// guarantees as the standard library: if the new data isn't valid UTF-8, none of
+/// it is appended, but, if there's an IO error, any valid data read before it is kept.
+///
+/// Unlike the standard library, we can't write into the `String`'s `Vec` without
+/// `unsafe`, so the existing contents are validated again. They're usually empty.
+pub(crate) fn append_to_string<F>(buf: &mut String, read_to_end: F) -> io::Result<usize>
+where
+    F: FnOnce(&mut Vec<u8>) -> io::Result<usize>,
+{
+    let mut bytes = std::mem::take(buf).into_bytes();
+    let start = bytes.len();
+    let ret = read_to_end(&mut bytes);
+
+    let valid = str::from_utf8(&bytes[start..]).is_ok();
+    if !valid {
+        bytes.truncate(start);
+    }
+    *buf = String::from_utf8(bytes).expect("only valid utf-8 remains");
+
+    if valid {
+        return ret;
+    }
+
+    ret.and_then(|_| {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        ))
+    })
+}
@@ -1,6 +1,12 @@
+use std::error;
+use std::fmt;
 use std::io;
 use std::io::BufRead;
 use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::ops::Deref;
+use std::ops::Range;
 
 /// An "extension" of `std::io::BufRead`, for which `fill_*` can be forced to read.
 ///
@@ -13,9 +19,30 @@ use std::io::Read;
 /// `target` bytes.
 ///
 /// `consume` advances the internal pointer, same as with `BufRead`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use iowrap::VarBufRead;
+/// use iowrap::VarBufReader;
+///
+/// let mut reader = VarBufReader::new(io::Cursor::new(b"\x00\x05hello world\n"));
+///
+/// let header = reader.fill_at_least(2).unwrap();
+/// let len = usize::from(u16::from_be_bytes([header[0], header[1]]));
+/// reader.consume(2);
+///
+/// assert_eq!(b"hello", &reader.fill_at_least(len).unwrap()[..len]);
+/// reader.consume(len + 1);
+///
+/// assert_eq!(b"world", reader.read_until_limit(b'\n', 100).unwrap().as_slice());
+/// ```
 pub trait VarBufRead {
     /// Advance the internal pointer, so that `fill_*` and `read*` will no longer return
     /// the consumed bytes.
+    ///
+    /// Implementations may panic if `amt` is more than is currently buffered.
     fn consume(&mut self, amt: usize);
 
     /// Try hard to return a buffer of at least `target` bytes. If the end of file is
@@ -25,6 +52,9 @@ pub trait VarBufRead {
     /// Other errors (except interruption) are returned as-is.
     fn fill_many(&mut self, target: usize) -> io::Result<&[u8]>;
 
+    /// The data which is currently buffered, without performing any reads.
+    fn buffer(&self) -> &[u8];
+
     /// Return a buffer of at least `target` bytes, by repeatedly reading from the
     /// underlying reader. If the underlying reader reaches end-of-file, an error will
     /// be returned.
@@ -38,9 +68,16 @@ pub trait VarBufRead {
         Ok(buf)
     }
 
-    /// Read
+    /// Read up to the next `delim`, looking at most `limit` bytes ahead.
+    ///
+    /// The `delim` is consumed, but is not included in the returned bytes.
+    ///
+    /// If there is no `delim` in the next `limit` bytes, or the stream ends before one
+    /// is found, then `ErrorKind::NotFound` is returned, and nothing is consumed.
+    /// `read_until_limit_or_eof` can tell these cases apart.
     fn read_until_limit(&mut self, delim: u8, limit: usize) -> Result<Vec<u8>, io::Error> {
         let buf = self.fill_many(limit)?;
+        let buf = &buf[..limit.min(buf.len())];
         if let Some(end) = memchr::memchr(delim, buf) {
             let ret = buf[..end].to_vec();
             self.consume(end + 1);
@@ -49,11 +86,287 @@ pub trait VarBufRead {
 
         Err(io::ErrorKind::NotFound.into())
     }
+
+    /// Read up to the next `delim`, looking at most `limit` bytes ahead, reporting
+    /// why a `delim` wasn't found, if it wasn't.
+    ///
+    /// If the stream ends before a `delim` is found, the remaining bytes are consumed and
+    /// returned as `Until::Eof`, so e.g. a final line without a terminator isn't lost.
+    ///
+    /// ```rust
+    /// # use std::io;
+    /// use iowrap::Until;
+    /// use iowrap::VarBufRead;
+    /// use iowrap::VarBufReader;
+    ///
+    /// let mut reader = VarBufReader::new(io::Cursor::new(b"one\nthree"));
+    /// assert_eq!(Until::Found(b"one".to_vec()), reader.read_until_limit_or_eof(b'\n', 4).unwrap());
+    /// assert_eq!(Until::LimitExceeded, reader.read_until_limit_or_eof(b'\n', 4).unwrap());
+    /// assert_eq!(Until::Eof(b"three".to_vec()), reader.read_until_limit_or_eof(b'\n', 6).unwrap());
+    /// assert_eq!(Until::Eof(Vec::new()), reader.read_until_limit_or_eof(b'\n', 6).unwrap());
+    /// ```
+    fn read_until_limit_or_eof(&mut self, delim: u8, limit: usize) -> Result<Until, io::Error> {
+        let buf = self.fill_many(limit)?;
+        let window = &buf[..limit.min(buf.len())];
+        if let Some(end) = memchr::memchr(delim, window) {
+            let ret = window[..end].to_vec();
+            self.consume(end + 1);
+            return Ok(Until::Found(ret));
+        }
+
+        if buf.len() < limit {
+            let ret = buf.to_vec();
+            self.consume(ret.len());
+            return Ok(Until::Eof(ret));
+        }
+
+        Ok(Until::LimitExceeded)
+    }
+
+    /// Like `read_until_limit`, but without copying.
+    ///
+    /// The returned `Record` borrows the bytes (not including the `delim`) from the
+    /// internal buffer, and consumes them, and the `delim`, when it is dropped.
+    ///
+    /// ```rust
+    /// # use std::io;
+    /// use iowrap::VarBufRead;
+    /// use iowrap::VarBufReader;
+    ///
+    /// let mut reader = VarBufReader::new(io::Cursor::new(b"one\ntwo\n"));
+    /// let mut total = 0;
+    /// while let Ok(line) = reader.read_until_limit_borrowed(b'\n', 100) {
+    ///     total += line.len();
+    /// }
+    /// assert_eq!(6, total);
+    /// ```
+    fn read_until_limit_borrowed(
+        &mut self,
+        delim: u8,
+        limit: usize,
+    ) -> Result<Record<'_, Self>, io::Error> {
+        let buf = self.fill_many(limit)?;
+        let buf = &buf[..limit.min(buf.len())];
+        if let Some(end) = memchr::memchr(delim, buf) {
+            return Ok(Record::new(self, 0..end, end + 1));
+        }
+
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    /// Read up to the next occurrence of the (multi-byte) `delim`, e.g. `b"\r\n"`,
+    /// looking at most `limit` bytes ahead. The whole `delim` must fit inside the `limit`.
+    ///
+    /// The `delim` is always consumed, and `Delimiter` decides whether it is also
+    /// included at the end of the returned bytes.
+    ///
+    /// If there is no `delim` in the next `limit` bytes, or the stream ends before one
+    /// is found, then `ErrorKind::NotFound` is returned, and nothing is consumed.
+    ///
+    /// An empty `delim` would be found everywhere, so is rejected with
+    /// `ErrorKind::InvalidInput`, without reading anything.
+    fn read_until_seq_limit(
+        &mut self,
+        delim: &[u8],
+        limit: usize,
+        keep: Delimiter,
+    ) -> Result<Vec<u8>, io::Error> {
+        if delim.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the delimiter is empty",
+            ));
+        }
+
+        let buf = self.fill_many(limit)?;
+        let buf = &buf[..limit.min(buf.len())];
+        if let Some(start) = memchr::memmem::find(buf, delim) {
+            let end = start + delim.len();
+            let ret = match keep {
+                Delimiter::Strip => buf[..start].to_vec(),
+                Delimiter::Keep => buf[..end].to_vec(),
+            };
+            self.consume(end);
+            return Ok(ret);
+        }
+
+        Err(io::ErrorKind::NotFound.into())
+    }
+
+    /// Read bytes for as long as `pred` returns `true`, looking at most `limit` bytes ahead.
+    ///
+    /// The first byte for which `pred` returns `false` is left in the stream. That byte
+    /// must be within the `limit`.
+    ///
+    /// If every byte in the next `limit` bytes matches, or the stream ends before a byte
+    /// which doesn't, then `ErrorKind::NotFound` is returned, and nothing is consumed.
+    fn read_while_limit<F: Fn(u8) -> bool>(
+        &mut self,
+        pred: F,
+        limit: usize,
+    ) -> Result<Vec<u8>, io::Error>
+    where
+        Self: Sized,
+    {
+        let buf = self.fill_many(limit)?;
+        let buf = &buf[..limit.min(buf.len())];
+        if let Some(end) = buf.iter().position(|&b| !pred(b)) {
+            let ret = buf[..end].to_vec();
+            self.consume(end);
+            return Ok(ret);
+        }
+
+        Err(io::ErrorKind::NotFound.into())
+    }
 }
 
+/// The outcome of `VarBufRead::read_until_limit_or_eof`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Until {
+    /// The bytes before the delimiter. The bytes and the delimiter have been consumed.
+    Found(Vec<u8>),
+    /// There was no delimiter in the first `limit` bytes. Nothing has been consumed.
+    LimitExceeded,
+    /// The stream ended before a delimiter. These are all the remaining bytes, and they
+    /// have been consumed. This is empty if there was nothing left at all.
+    Eof(Vec<u8>),
+}
+
+/// Bytes borrowed from the front of a `VarBufRead`'s buffer.
+///
+/// The bytes are consumed from the reader when this is dropped.
+pub struct Record<'a, B: VarBufRead + ?Sized> {
+    reader: &'a mut B,
+    range: Range<usize>,
+    consume: usize,
+}
+
+impl<'a, B: VarBufRead + ?Sized> Record<'a, B> {
+    /// Expose `range` of the `reader`'s buffer, then consume `consume` bytes.
+    pub(crate) fn new(reader: &'a mut B, range: Range<usize>, consume: usize) -> Self {
+        debug_assert!(range.end <= consume && consume <= reader.buffer().len());
+        Record {
+            reader,
+            range,
+            consume,
+        }
+    }
+}
+
+impl<B: VarBufRead + ?Sized> Deref for Record<'_, B> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.reader.buffer()[self.range.clone()]
+    }
+}
+
+impl<B: VarBufRead + ?Sized> Drop for Record<'_, B> {
+    fn drop(&mut self) {
+        self.reader.consume(self.consume);
+    }
+}
+
+impl<B: VarBufRead + ?Sized> fmt::Debug for Record<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Record").field(&self.deref()).finish()
+    }
+}
+
+/// Whether `VarBufRead::read_until_seq_limit` returns the delimiter it found.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Consume the delimiter, but don't include it in the returned bytes.
+    Strip,
+    /// Consume the delimiter, and include it at the end of the returned bytes.
+    Keep,
+}
+
+/// The smallest buffer we will allocate, so tiny `fill_many` requests still read a
+/// reasonable amount from the underlying reader.
+const MIN_CAPACITY: usize = 8 * 1024;
+
+/// A `BufRead` with a buffer which grows to whatever size `VarBufRead` asks for.
+///
+/// Unlike `std::io::BufReader`, the buffer starts empty, and grows, by doubling from
+/// 8KiB, only while it is asked for, and actually receives, more data.
+///
+/// Reads go directly into the spare space at the end of the buffer. Consumed bytes
+/// are not removed until the buffer runs out of space at the end, at which point
+/// the remaining data is moved back to the start.
+///
+/// By default, the buffer will grow without limit. If the sizes requested come from
+/// untrusted input, e.g. a length prefix, then set a `with_max_capacity`.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use iowrap::CapacityExceeded;
+/// use iowrap::VarBufRead;
+/// use iowrap::VarBufReader;
+///
+/// let mut reader = VarBufReader::new(io::Cursor::new(vec![0u8; 100])).with_max_capacity(64);
+/// assert_eq!(64, reader.fill_at_least(64).unwrap().len());
+///
+/// let err = reader.fill_at_least(65).unwrap_err();
+/// let cause = err.get_ref().and_then(|e| e.downcast_ref::<CapacityExceeded>());
+/// assert_eq!(Some(65), cause.map(|c| c.requested()));
+/// ```
 pub struct VarBufReader<R> {
     inner: R,
+    /// Allocated and initialised storage; only `pos..filled` is live data.
     data: Vec<u8>,
+    pos: usize,
+    filled: usize,
+    max_capacity: usize,
+    /// The position of the underlying reader, i.e. of `data[filled]`, if we know it.
+    inner_pos: Option<u64>,
+}
+
+/// The error payload when a `VarBufReader` is asked to buffer more than its maximum capacity,
+/// an `Unread` is asked to hold more than its capacity, or an `Eof` is asked to `peek`
+/// further than its limit.
+///
+/// From a `VarBufReader` or `Eof`, this is returned inside an `io::Error` of kind `ErrorKind::OutOfMemory`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CapacityExceeded {
+    requested: usize,
+    limit: usize,
+}
+
+impl CapacityExceeded {
+    pub(crate) fn new(requested: usize, limit: usize) -> Self {
+        CapacityExceeded { requested, limit }
+    }
+
+    /// The number of bytes which would have needed to be held.
+    pub fn requested(&self) -> usize {
+        self.requested
+    }
+
+    /// The configured maximum number of bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "requested {} bytes, but the limit is {} bytes",
+            self.requested, self.limit
+        )
+    }
+}
+
+impl error::Error for CapacityExceeded {}
+
+impl From<CapacityExceeded> for io::Error {
+    fn from(e: CapacityExceeded) -> io::Error {
+        io::Error::new(io::ErrorKind::OutOfMemory, e)
+    }
 }
 
 impl<R: Read> VarBufReader<R> {
@@ -61,27 +374,182 @@ impl<R: Read> VarBufReader<R> {
         VarBufReader {
             inner,
             data: Vec::new(),
+            pos: 0,
+            filled: 0,
+            max_capacity: usize::MAX,
+            inner_pos: None,
         }
     }
+
+    /// Refuse to buffer more than `max_capacity` bytes.
+    ///
+    /// `fill_many` (and everything built on it) will fail with a `CapacityExceeded`
+    /// if asked for more, instead of allocating.
+    pub fn with_max_capacity(mut self, max_capacity: usize) -> Self {
+        assert!(
+            max_capacity >= self.filled - self.pos,
+            "more than max_capacity is already buffered"
+        );
+        self.max_capacity = max_capacity;
+        self.shrink_to(max_capacity.min(self.capacity()));
+        self
+    }
+}
+
+impl<R> VarBufReader<R> {
+    /// The data which has been read from the underlying reader, but not yet consumed.
+    ///
+    /// This is the same as `VarBufRead::buffer`.
+    pub fn buffer(&self) -> &[u8] {
+        &self.data[self.pos..self.filled]
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Reading directly from the underlying reader will skip over the buffered data.
+    pub fn get_mut(&mut self) -> &mut R {
+        // they might move it, so we can no longer trust our idea of where it is
+        self.inner_pos = None;
+        &mut self.inner
+    }
+
+    /// Any buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// The number of bytes the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// The most bytes we will buffer, as configured by `with_max_capacity`.
+    pub fn max_capacity(&self) -> usize {
+        self.max_capacity
+    }
+
+    /// Reduce the capacity to the larger of `min_capacity` and the amount of data buffered,
+    /// e.g. to release memory after an unusually large `fill_many`.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let live = self.filled - self.pos;
+        let new_len = live.max(min_capacity);
+        if new_len >= self.data.len() {
+            return;
+        }
+
+        let mut data = vec![0u8; new_len];
+        data[..live].copy_from_slice(self.buffer());
+        self.data = data;
+        self.pos = 0;
+        self.filled = live;
+    }
+
+    /// Reduce the capacity to the amount of data buffered.
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0)
+    }
+
+    /// Record that `amt` bytes have been read from the underlying reader.
+    fn advance_inner(&mut self, amt: usize) {
+        if let Some(inner_pos) = self.inner_pos.as_mut() {
+            *inner_pos += amt as u64;
+        }
+    }
+
+    /// Make space to read more of the `target` bytes starting at `pos`, when fewer
+    /// than `target` are buffered.
+    ///
+    /// The buffer at most doubles each time, so a huge `target` only costs memory as
+    /// the data actually arrives. The live data is only moved back to the front if
+    /// we've consumed at least as much as we'd be moving, otherwise we grow, so the
+    /// copying is amortised.
+    fn make_room(&mut self, target: usize) -> Result<(), CapacityExceeded> {
+        if target > self.max_capacity {
+            return Err(CapacityExceeded::new(target, self.max_capacity));
+        }
+
+        if self.data.len() - self.pos >= target {
+            return Ok(());
+        }
+
+        if self.data.len() < target && self.filled < self.data.len() {
+            // we'll have to grow anyway; fill what we have first
+            return Ok(());
+        }
+
+        let live = self.filled - self.pos;
+        if self.data.len() < target || (self.pos < live && self.data.len() < self.max_capacity) {
+            let new_len = self
+                .data
+                .len()
+                .saturating_mul(2)
+                .max(MIN_CAPACITY)
+                .min(self.max_capacity);
+            let mut data = vec![0u8; new_len];
+            data[..live].copy_from_slice(self.buffer());
+            self.data = data;
+        } else {
+            self.data.copy_within(self.pos..self.filled, 0);
+        }
+
+        self.pos = 0;
+        self.filled = live;
+        Ok(())
+    }
+}
+
+impl<R: Read> VarBufReader<R> {
+    /// Read directly into `buf`, bypassing the (empty) buffer, until it is full,
+    /// or the underlying reader has nothing more to give.
+    fn read_direct(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut done = 0;
+        while done < buf.len() {
+            match self.inner.read(&mut buf[done..]) {
+                Ok(0) => break,
+                Ok(read) => {
+                    done += read;
+                    self.advance_inner(read);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) if done > 0 => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(done)
+    }
 }
 
 impl<R: Read> VarBufRead for VarBufReader<R> {
     fn consume(&mut self, amt: usize) {
-        assert!(amt <= self.data.len());
-        self.data.drain(..amt);
+        assert!(amt <= self.filled - self.pos);
+        self.pos += amt;
+        if self.pos == self.filled {
+            self.pos = 0;
+            self.filled = 0;
+        }
     }
 
     fn fill_many(&mut self, target: usize) -> Result<&[u8], io::Error> {
-        while self.data.len() < target {
-            let mut buf = [0u8; 8 * 1024];
-            let read = self.inner.read(&mut buf)?;
-            if 0 == read {
-                break;
+        while self.filled - self.pos < target {
+            self.make_room(target)?;
+            match self.inner.read(&mut self.data[self.filled..]) {
+                Ok(0) => break,
+                Ok(read) => {
+                    self.filled += read;
+                    self.advance_inner(read);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
             }
-            self.data.extend(&buf[..read]);
         }
 
-        Ok(&self.data)
+        Ok(VarBufReader::buffer(self))
+    }
+
+    fn buffer(&self) -> &[u8] {
+        VarBufReader::buffer(self)
     }
 }
 
@@ -96,12 +564,142 @@ impl<R: Read> BufRead for VarBufReader<R> {
 }
 
 impl<R: Read> Read for VarBufReader<R> {
+    /// Fill `buf` as far as possible, stopping early only at end-of-file, or on error.
+    ///
+    /// Large reads, which would not fit in the buffer, bypass it entirely.
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
-        let found = self.fill_many(buf.len())?;
-        let valid = buf.len().min(found.len());
-        buf[..valid].copy_from_slice(&found[..valid]);
+        let buffered = self.buffer();
+        let done = buf.len().min(buffered.len());
+        buf[..done].copy_from_slice(&buffered[..done]);
+        VarBufRead::consume(self, done);
+
+        let rest = &mut buf[done..];
+        if rest.is_empty() {
+            return Ok(done);
+        }
+
+        let capacity = self.data.len().max(MIN_CAPACITY).min(self.max_capacity);
+        if rest.len() >= capacity {
+            return match self.read_direct(rest) {
+                Ok(read) => Ok(done + read),
+                Err(_) if done > 0 => Ok(done),
+                Err(e) => Err(e),
+            };
+        }
+
+        let found = match self.fill_many(rest.len()) {
+            Ok(found) => found,
+            Err(_) if done > 0 => return Ok(done),
+            Err(e) => return Err(e),
+        };
+        let valid = rest.len().min(found.len());
+        rest[..valid].copy_from_slice(&found[..valid]);
         VarBufRead::consume(self, valid);
-        Ok(valid)
+        Ok(done + valid)
+    }
+}
+
+impl<R: Read + Seek> VarBufReader<R> {
+    /// Seek relative to the current position, without discarding the buffer if the
+    /// target is inside it (including the already consumed part).
+    pub fn seek_relative(&mut self, offset: i64) -> io::Result<()> {
+        if self.seek_in_buffer(offset) {
+            return Ok(());
+        }
+
+        self.seek(SeekFrom::Current(offset)).map(|_| ())
+    }
+
+    /// Move `pos` by `offset`, if that stays inside the data we have, returning success.
+    fn seek_in_buffer(&mut self, offset: i64) -> bool {
+        match (self.pos as i64).checked_add(offset) {
+            Some(pos) if pos >= 0 && pos <= self.filled as i64 => {
+                self.pos = pos as usize;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<R: Read + Seek> Seek for VarBufReader<R> {
+    /// Seeking to a position which is already buffered doesn't touch the underlying
+    /// reader, see `seek_relative`. Otherwise, the buffer is discarded.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let relative = match pos {
+            SeekFrom::Start(target) => {
+                let current = self.stream_position()?;
+                let offset = i64::try_from(i128::from(target) - i128::from(current));
+                if let Ok(offset) = offset {
+                    if self.seek_in_buffer(offset) {
+                        return Ok(target);
+                    }
+                }
+                None
+            }
+            SeekFrom::Current(offset) => {
+                if self.seek_in_buffer(offset) {
+                    return self.stream_position();
+                }
+                Some(offset)
+            }
+            SeekFrom::End(_) => None,
+        };
+
+        let remaining = (self.filled - self.pos) as i64;
+        let result = match relative {
+            Some(offset) => match offset.checked_sub(remaining) {
+                Some(offset) => self.inner.seek(SeekFrom::Current(offset)),
+                None => self
+                    .inner
+                    .seek(SeekFrom::Current(-remaining))
+                    .and_then(|_| {
+                        // the buffer no longer follows the underlying reader, even if
+                        // the second step fails
+                        self.pos = 0;
+                        self.filled = 0;
+                        self.inner.seek(SeekFrom::Current(offset))
+                    }),
+            },
+            None => self.inner.seek(pos),
+        };
+
+        match result {
+            Ok(new) => {
+                self.pos = 0;
+                self.filled = 0;
+                self.inner_pos = Some(new);
+                Ok(new)
+            }
+            Err(e) => {
+                self.inner_pos = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// This only asks the underlying reader if we don't already know where it is,
+    /// i.e. once at the start, or after `get_mut`.
+    ///
+    /// If the underlying reader has been moved back, through `get_mut`, to before
+    /// the start of the data we have buffered, there is no position, and this errors.
+    fn stream_position(&mut self) -> io::Result<u64> {
+        let inner_pos = match self.inner_pos {
+            Some(inner_pos) => inner_pos,
+            None => {
+                let inner_pos = self.inner.stream_position()?;
+                self.inner_pos = Some(inner_pos);
+                inner_pos
+            }
+        };
+        inner_pos
+            .checked_sub((self.filled - self.pos) as u64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "the underlying reader is behind the buffered data",
+                )
+            })
     }
 }
 
@@ -110,11 +708,16 @@ mod tests {
     use std::io;
     use std::io::Cursor;
     use std::io::Read;
+    use std::io::Seek;
+    use std::io::SeekFrom;
 
     use byteorder::ReadBytesExt;
 
     use crate::ShortRead;
 
+    use super::CapacityExceeded;
+    use super::Delimiter;
+    use super::Until;
     use super::VarBufRead;
     use super::VarBufReader;
 
@@ -158,6 +761,21 @@ mod tests {
         assert_eq!(b'o', vb.read_u8().unwrap());
     }
 
+    #[test]
+    fn read_until_stays_within_limit() {
+        let mut vb = VarBufReader::new(Cursor::new(b"hello world\nbye"));
+        assert_eq!(b"hello world\nbye", vb.fill_many(1).unwrap());
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_limit(b'\n', 5).unwrap_err().kind(),
+            "the delimiter is buffered, but beyond the limit"
+        );
+        assert_eq!(
+            b"hello world",
+            vb.read_until_limit(b'\n', 12).unwrap().as_slice()
+        );
+    }
+
     #[test]
     fn eof() {
         let mut vb = VarBufReader::new(ShortRead::new(
@@ -215,4 +833,393 @@ mod tests {
         );
         assert_eq!(b"world", &vb.fill_many(5).unwrap()[..5]);
     }
+
+    #[test]
+    fn interrupted() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"hello world"),
+            vec![0, 2, 0, 0, 3, 0, 99].into_iter(),
+        ));
+        assert_eq!(b"hello", &vb.fill_at_least(5).unwrap());
+        assert_eq!(b"hello", vb.buffer());
+        assert_eq!(b"hello", vb.read_until_limit(b' ', 6).unwrap().as_slice());
+        assert_eq!(b"world", &vb.fill_many(100).unwrap());
+    }
+
+    #[test]
+    fn accessors() {
+        let mut vb = VarBufReader::new(Cursor::new(b"hello world"));
+        assert_eq!(b"", vb.buffer());
+        assert_eq!(b"hello", &vb.fill_at_least(5).unwrap()[..5]);
+        vb.consume(6);
+        assert_eq!(b"world", vb.buffer());
+
+        assert_eq!(11, vb.get_ref().position());
+        vb.get_mut().set_position(0);
+        assert_eq!(b"hello", &vb.into_inner().get_ref()[..5]);
+    }
+
+    #[test]
+    fn refill_after_consuming_most_of_the_buffer() {
+        let data: Vec<u8> = (0..30_000u32).map(|i| i as u8).collect();
+        let mut vb = VarBufReader::new(Cursor::new(data.clone()));
+        let mut pos = 0;
+        while pos < data.len() {
+            let want = 1000.min(data.len() - pos);
+            assert_eq!(
+                &data[pos..pos + want],
+                &vb.fill_at_least(want).unwrap()[..want]
+            );
+            vb.consume(want - 1);
+            pos += want - 1;
+            assert_eq!(data[pos], vb.read_u8().unwrap());
+            pos += 1;
+        }
+        assert_eq!(b"", vb.fill_many(1).unwrap());
+    }
+
+    #[test]
+    fn large_read_after_small() {
+        let data: Vec<u8> = (0..30_000u32).map(|i| i as u8).collect();
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(data.clone()),
+            vec![10, 0, 5000, 5000, 0]
+                .into_iter()
+                .chain(std::iter::repeat(99_999)),
+        ));
+        assert_eq!(data[0], vb.read_u8().unwrap());
+
+        let mut buf = vec![0u8; 20_000];
+        assert_eq!(20_000, vb.read(&mut buf).unwrap());
+        assert_eq!(&data[1..20_001], buf.as_slice());
+
+        let mut rest = Vec::new();
+        vb.read_to_end(&mut rest).unwrap();
+        assert_eq!(&data[20_001..], rest.as_slice());
+    }
+
+    #[test]
+    fn max_capacity() {
+        let mut vb =
+            VarBufReader::new(Cursor::new(b"hello there world".to_vec())).with_max_capacity(12);
+        assert_eq!(12, vb.max_capacity());
+        assert_eq!(b"hello", &vb.fill_at_least(5).unwrap()[..5]);
+        assert!(vb.capacity() <= 12);
+
+        let err = vb.fill_many(13).unwrap_err();
+        assert_eq!(io::ErrorKind::OutOfMemory, err.kind());
+        let cause = err
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CapacityExceeded>())
+            .expect("typed error");
+        assert_eq!(13, cause.requested());
+        assert_eq!(12, cause.limit());
+
+        assert_eq!(b"hello there ", &vb.fill_many(12).unwrap());
+        vb.consume(6);
+        assert_eq!(b"there world", &vb.fill_at_least(11).unwrap()[..11]);
+        assert!(vb.capacity() <= 12);
+
+        let mut rest = Vec::new();
+        vb.read_to_end(&mut rest).unwrap();
+        assert_eq!(b"there world", rest.as_slice());
+    }
+
+    #[test]
+    fn huge_target_on_a_short_stream() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"four"),
+            vec![1, 3].into_iter().chain(std::iter::repeat(99)),
+        ));
+        assert_eq!(b"four", &vb.fill_many(1 << 46).unwrap());
+        assert!(vb.capacity() < 1 << 20, "grew only as the data arrived");
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_limit(b'\n', usize::MAX).unwrap_err().kind()
+        );
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            vb.fill_at_least(usize::MAX).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn shrink() {
+        let mut vb = VarBufReader::new(Cursor::new(vec![5u8; 100_000]));
+        assert_eq!(100_000, vb.fill_at_least(100_000).unwrap().len());
+        assert!(vb.capacity() >= 100_000);
+        vb.consume(99_990);
+
+        vb.shrink_to(1000);
+        assert_eq!(1000, vb.capacity());
+        assert_eq!(&[5u8; 10], vb.buffer());
+
+        vb.shrink_to_fit();
+        assert_eq!(10, vb.capacity());
+        assert_eq!(&[5u8; 10], vb.buffer());
+
+        vb.consume(10);
+        vb.shrink_to_fit();
+        assert_eq!(0, vb.capacity());
+        assert_eq!(b"", vb.fill_many(1).unwrap());
+    }
+
+    #[test]
+    fn read_seq() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"GET / HTTP/1.1\r\nHost: a\r\n\r\nbody"),
+            vec![3, 1, 0, 4, 1, 99].into_iter(),
+        ));
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_seq_limit(b"\r\n", 15, Delimiter::Strip)
+                .unwrap_err()
+                .kind(),
+            "the delimiter doesn't entirely fit in the limit"
+        );
+        assert_eq!(
+            b"GET / HTTP/1.1",
+            vb.read_until_seq_limit(b"\r\n", 16, Delimiter::Strip)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            b"Host: a\r\n\r\n",
+            vb.read_until_seq_limit(b"\r\n\r\n", 100, Delimiter::Keep)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_seq_limit(b"\r\n", 100, Delimiter::Keep)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            vb.read_until_seq_limit(b"", 100, Delimiter::Keep)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(b"body", vb.buffer());
+    }
+
+    #[test]
+    fn read_while() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"12345abc678"),
+            vec![1, 0, 2, 1, 99].into_iter(),
+        ));
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_while_limit(|b| b.is_ascii_digit(), 5)
+                .unwrap_err()
+                .kind()
+        );
+        assert_eq!(
+            b"12345",
+            vb.read_while_limit(|b| b.is_ascii_digit(), 6)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            b"",
+            vb.read_while_limit(|b| b.is_ascii_digit(), 6)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            b"abc",
+            vb.read_while_limit(|b| b.is_ascii_alphabetic(), 6)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_while_limit(|b| b.is_ascii_digit(), 100)
+                .unwrap_err()
+                .kind(),
+            "end of file is not a terminator"
+        );
+        assert_eq!(b"678", vb.buffer());
+    }
+
+    #[test]
+    fn read_borrowed() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"hello there world"),
+            vec![1, 1, 2, 1, 0, 99].into_iter(),
+        ));
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_limit_borrowed(b' ', 5).unwrap_err().kind()
+        );
+        {
+            let record = vb.read_until_limit_borrowed(b' ', 6).unwrap();
+            assert_eq!(b"hello", &*record);
+        }
+        assert_eq!(b"there", &*vb.read_until_limit_borrowed(b' ', 6).unwrap());
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            vb.read_until_limit_borrowed(b' ', 200).unwrap_err().kind()
+        );
+        assert_eq!(b"world", vb.buffer());
+    }
+
+    #[test]
+    fn read_until_or_eof() {
+        let mut vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"hello there world"),
+            vec![1, 1, 2, 1, 0, 99].into_iter(),
+        ));
+        assert_eq!(
+            Until::LimitExceeded,
+            vb.read_until_limit_or_eof(b' ', 5).unwrap()
+        );
+        assert_eq!(
+            Until::Found(b"hello".to_vec()),
+            vb.read_until_limit_or_eof(b' ', 6).unwrap()
+        );
+        assert_eq!(
+            Until::Found(b"there".to_vec()),
+            vb.read_until_limit_or_eof(b' ', 6).unwrap()
+        );
+        assert_eq!(
+            Until::LimitExceeded,
+            vb.read_until_limit_or_eof(b' ', 5).unwrap(),
+            "exactly the limit is left, and there's no delimiter in it"
+        );
+        assert_eq!(
+            Until::Eof(b"world".to_vec()),
+            vb.read_until_limit_or_eof(b' ', 6).unwrap()
+        );
+        assert_eq!(
+            Until::Eof(Vec::new()),
+            vb.read_until_limit_or_eof(b' ', 6).unwrap()
+        );
+    }
+
+    /// Count how many times the underlying reader is asked to seek.
+    struct CountSeeks<R> {
+        inner: R,
+        seeks: usize,
+    }
+
+    impl<R: Read> Read for CountSeeks<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl<R: Seek> Seek for CountSeeks<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.seeks += 1;
+            self.inner.seek(pos)
+        }
+    }
+
+    #[test]
+    fn seek_in_buffer() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut vb = VarBufReader::new(CountSeeks {
+            inner: Cursor::new(data),
+            seeks: 0,
+        });
+
+        assert_eq!(0, vb.stream_position().unwrap());
+        assert_eq!(1, vb.get_ref().seeks, "we have to ask where we start");
+
+        assert_eq!(&[0, 1, 2], &vb.fill_at_least(15).unwrap()[..3]);
+        vb.consume(5);
+        assert_eq!(5, vb.stream_position().unwrap());
+        assert_eq!(1, vb.get_ref().seeks, "we know where we are now");
+
+        vb.seek_relative(-3).unwrap();
+        assert_eq!(2, vb.read_u8().unwrap());
+        assert_eq!(13, vb.seek(SeekFrom::Current(10)).unwrap());
+        assert_eq!(13, vb.read_u8().unwrap());
+        assert_eq!(1, vb.seek(SeekFrom::Start(1)).unwrap());
+        assert_eq!(1, vb.read_u8().unwrap());
+        assert_eq!(100, vb.seek(SeekFrom::Start(100)).unwrap());
+        assert_eq!(100, vb.stream_position().unwrap());
+        assert_eq!(b"", vb.buffer(), "exactly the end of the buffer");
+        assert_eq!(1, vb.get_ref().seeks, "all of that was in the buffer");
+
+        vb.seek_relative(-80).unwrap();
+        assert_eq!(20, vb.read_u8().unwrap());
+        assert_eq!(21, vb.stream_position().unwrap());
+        assert_eq!(1, vb.get_ref().seeks);
+    }
+
+    #[test]
+    fn seek_outside_buffer() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut vb = VarBufReader::new(CountSeeks {
+            inner: Cursor::new(data),
+            seeks: 0,
+        })
+        .with_max_capacity(10);
+
+        assert_eq!(10, vb.fill_at_least(10).unwrap().len());
+        vb.consume(3);
+        assert_eq!(3, vb.read_u8().unwrap());
+        assert_eq!(0, vb.get_ref().seeks);
+
+        assert_eq!(54, vb.seek(SeekFrom::Current(50)).unwrap());
+        assert_eq!(1, vb.get_ref().seeks);
+        assert_eq!(b"", vb.buffer(), "the buffer was discarded");
+        assert_eq!(54, vb.read_u8().unwrap());
+        assert_eq!(55, vb.stream_position().unwrap());
+        assert_eq!(1, vb.get_ref().seeks);
+
+        assert_eq!(98, vb.seek(SeekFrom::End(-2)).unwrap());
+        assert_eq!(98, vb.read_u8().unwrap());
+        assert_eq!(5, vb.seek(SeekFrom::Start(5)).unwrap());
+        assert_eq!(5, vb.read_u8().unwrap());
+        vb.seek_relative(-6).unwrap();
+        assert_eq!(0, vb.read_u8().unwrap());
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            vb.seek(SeekFrom::Current(-2)).unwrap_err().kind()
+        );
+
+        let buffered = vb.buffer().len() as u64;
+        vb.get_mut().inner.set_position(40);
+        assert_eq!(40 - buffered, vb.stream_position().unwrap());
+    }
+
+    #[test]
+    fn stream_position_behind_buffer() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut vb = VarBufReader::new(Cursor::new(data)).with_max_capacity(10);
+        assert_eq!(10, vb.fill_at_least(10).unwrap().len());
+        vb.get_mut().set_position(0);
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            vb.stream_position().unwrap_err().kind(),
+            "ten bytes buffered from before the start"
+        );
+
+        vb.get_mut().set_position(12);
+        assert_eq!(2, vb.stream_position().unwrap());
+    }
+
+    #[test]
+    fn seek_fails_after_stepping_back() {
+        let data: Vec<u8> = (0..100u8).collect();
+        let mut vb = VarBufReader::new(Cursor::new(data)).with_max_capacity(10);
+        assert_eq!(10, vb.fill_at_least(10).unwrap().len());
+        vb.consume(3);
+
+        // too far to do in one step from the underlying reader's position, and then
+        // the second step fails
+        assert_eq!(
+            io::ErrorKind::InvalidInput,
+            vb.seek(SeekFrom::Current(i64::MIN + 1)).unwrap_err().kind()
+        );
+        assert_eq!(b"", vb.buffer(), "the buffer was discarded");
+        assert_eq!(3, vb.stream_position().unwrap());
+        assert_eq!(3, vb.read_u8().unwrap());
+        assert_eq!(4, vb.read_u8().unwrap());
+    }
 }
@@ -0,0 +1,271 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+
+use crate::var_buf::VarBufRead;
+
+/// Everything is already in memory, so `fill_many` never has any work to do.
+impl VarBufRead for &[u8] {
+    fn consume(&mut self, amt: usize) {
+        *self = &self[amt..];
+    }
+
+    fn fill_many(&mut self, _target: usize) -> io::Result<&[u8]> {
+        Ok(self)
+    }
+
+    fn buffer(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Everything is already in memory, so `fill_many` never has any work to do.
+impl<T: AsRef<[u8]>> VarBufRead for io::Cursor<T> {
+    fn consume(&mut self, amt: usize) {
+        assert!(amt <= VarBufRead::buffer(self).len());
+        self.set_position(self.position() + amt as u64);
+    }
+
+    fn fill_many(&mut self, _target: usize) -> io::Result<&[u8]> {
+        Ok(VarBufRead::buffer(self))
+    }
+
+    fn buffer(&self) -> &[u8] {
+        let data = self.get_ref().as_ref();
+        let pos = usize::try_from(self.position()).unwrap_or(usize::MAX);
+        &data[pos.min(data.len())..]
+    }
+}
+
+impl<B: VarBufRead + ?Sized> VarBufRead for &mut B {
+    fn consume(&mut self, amt: usize) {
+        (**self).consume(amt)
+    }
+
+    fn fill_many(&mut self, target: usize) -> io::Result<&[u8]> {
+        (**self).fill_many(target)
+    }
+
+    fn buffer(&self) -> &[u8] {
+        (**self).buffer()
+    }
+}
+
+/// Add `VarBufRead` to an existing `std::io::BufReader`.
+///
+/// `BufReader`'s buffer can't be resized, so, when asked for more than it has buffered,
+/// its contents are moved into a second buffer, which is grown as necessary. When this
+/// is all consumed, requests are served directly from the `BufReader` again.
+///
+/// # Example
+///
+/// ```rust
+/// # use std::io;
+/// use iowrap::VarBufAdaptor;
+/// use iowrap::VarBufRead;
+///
+/// let reader = io::BufReader::with_capacity(2, io::Cursor::new(b"hello world"));
+/// let mut reader = VarBufAdaptor::new(reader);
+/// assert_eq!(b"hello", &reader.fill_at_least(5).unwrap()[..5]);
+/// ```
+pub struct VarBufAdaptor<R> {
+    inner: io::BufReader<R>,
+    spill: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> VarBufAdaptor<R> {
+    pub fn new(inner: io::BufReader<R>) -> Self {
+        VarBufAdaptor {
+            inner,
+            spill: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// `BufReader::fill_buf`, retrying on interruption, returning only the length,
+    /// so the `BufReader` isn't left borrowed.
+    fn fill_inner(&mut self) -> io::Result<usize> {
+        loop {
+            match self.inner.fill_buf() {
+                Ok(buf) => return Ok(buf.len()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R> VarBufAdaptor<R> {
+    pub fn get_ref(&self) -> &io::BufReader<R> {
+        &self.inner
+    }
+
+    /// Reading directly from the `BufReader` will skip over any data in our own buffer.
+    pub fn get_mut(&mut self) -> &mut io::BufReader<R> {
+        &mut self.inner
+    }
+
+    /// Any data which has been moved out of the `BufReader` is lost.
+    pub fn into_inner(self) -> io::BufReader<R> {
+        self.inner
+    }
+}
+
+impl<R: Read> VarBufRead for VarBufAdaptor<R> {
+    fn consume(&mut self, amt: usize) {
+        if self.spill.is_empty() {
+            assert!(amt <= self.inner.buffer().len());
+            self.inner.consume(amt);
+            return;
+        }
+
+        assert!(amt <= self.spill.len() - self.pos);
+        self.pos += amt;
+        if self.pos == self.spill.len() {
+            self.spill.clear();
+            self.pos = 0;
+        }
+    }
+
+    fn fill_many(&mut self, target: usize) -> io::Result<&[u8]> {
+        if self.spill.is_empty() {
+            let available = self.fill_inner()?;
+            if available >= target || 0 == available {
+                return Ok(self.inner.buffer());
+            }
+
+            // the `BufReader` will not read any more until we consume what it has
+            self.spill.extend_from_slice(self.inner.buffer());
+            self.inner.consume(available);
+        }
+
+        if self.spill.len() - self.pos < target && self.pos >= self.spill.len() - self.pos {
+            self.spill.drain(..self.pos);
+            self.pos = 0;
+        }
+
+        while self.spill.len() - self.pos < target {
+            let available = self.fill_inner()?;
+            if 0 == available {
+                break;
+            }
+            self.spill.extend_from_slice(self.inner.buffer());
+            self.inner.consume(available);
+        }
+
+        Ok(&self.spill[self.pos..])
+    }
+
+    fn buffer(&self) -> &[u8] {
+        if self.spill.is_empty() {
+            self.inner.buffer()
+        } else {
+            &self.spill[self.pos..]
+        }
+    }
+}
+
+impl<R: Read> BufRead for VarBufAdaptor<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.fill_many(1)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        VarBufRead::consume(self, amt)
+    }
+}
+
+impl<R: Read> Read for VarBufAdaptor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let found = self.fill_many(1)?;
+        let valid = buf.len().min(found.len());
+        buf[..valid].copy_from_slice(&found[..valid]);
+        VarBufRead::consume(self, valid);
+        Ok(valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::Cursor;
+
+    use crate::ShortRead;
+    use crate::VarBufRead;
+    use crate::VarBufReader;
+
+    use super::VarBufAdaptor;
+
+    fn words<B: VarBufRead>(mut from: B) -> Vec<Vec<u8>> {
+        let mut ret = Vec::new();
+        while let Ok(word) = from.read_until_limit(b' ', 10) {
+            ret.push(word);
+        }
+        ret.push(from.fill_many(10).unwrap().to_vec());
+        ret
+    }
+
+    fn expected() -> Vec<Vec<u8>> {
+        vec![b"hello".to_vec(), b"there".to_vec(), b"world".to_vec()]
+    }
+
+    #[test]
+    fn slice() {
+        let mut data: &[u8] = b"hello there world";
+        assert_eq!(expected(), words(&mut data));
+        assert_eq!(b"world", data);
+        assert_eq!(expected(), words(&b"hello there world"[..]));
+    }
+
+    #[test]
+    fn cursor() {
+        let mut cursor = Cursor::new(b"hello there world");
+        assert_eq!(expected(), words(&mut cursor));
+        assert_eq!(12, cursor.position());
+
+        cursor.set_position(100);
+        assert_eq!(b"", VarBufRead::buffer(&cursor));
+        assert_eq!(
+            io::ErrorKind::UnexpectedEof,
+            cursor.fill_at_least(1).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn var_buf_reader() {
+        let vb = VarBufReader::new(ShortRead::new(
+            Cursor::new(b"hello there world"),
+            vec![1, 1, 2, 1, 0, 99].into_iter(),
+        ));
+        assert_eq!(expected(), words(vb));
+    }
+
+    #[test]
+    fn adaptor() {
+        let reader = io::BufReader::with_capacity(
+            3,
+            ShortRead::new(
+                Cursor::new(b"hello there world"),
+                vec![1, 1, 2, 0, 1].into_iter().chain(std::iter::repeat(3)),
+            ),
+        );
+        assert_eq!(expected(), words(VarBufAdaptor::new(reader)));
+    }
+
+    #[test]
+    fn adaptor_returns_to_the_bufreader() {
+        let reader = io::BufReader::with_capacity(4, Cursor::new(b"hello there world"));
+        let mut adaptor = VarBufAdaptor::new(reader);
+        assert_eq!(b"hello there ", adaptor.fill_at_least(11).unwrap());
+        assert_eq!(b"hello there ", adaptor.buffer());
+        adaptor.consume(11);
+        assert_eq!(b" worl", adaptor.fill_many(2).unwrap());
+        adaptor.consume(5);
+
+        assert_eq!(b"d", adaptor.fill_many(1).unwrap());
+        assert_eq!(b"d", adaptor.get_ref().buffer());
+        adaptor.consume(1);
+        assert_eq!(b"", adaptor.fill_many(1).unwrap());
+    }
+}